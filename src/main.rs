@@ -1,9 +1,19 @@
-use reqwest::header::HeaderMap;
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, Utc};
+use futures::StreamExt;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::{DeserializeOwned, DeserializeSeed, IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::error::Error;
+use std::io::{Read, Write};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
+mod select;
+mod tui;
+
 #[derive(StructOpt, Debug, Clone)]
 #[structopt(
     name = "zenhub",
@@ -19,32 +29,1009 @@ struct Opt {
     )]
     api_root: String,
     /// zen hub workspace ID
-    #[structopt(long, env = "ZENHUB_WORKSPACE_ID")]
+    #[structopt(
+        long,
+        env = "ZENHUB_WORKSPACE_ID",
+        default_value = "",
+        hide_default_value = true
+    )]
     workspace_id: String,
 
+    /// zen hub workspace name, resolved to its ID at runtime
+    #[structopt(long, env = "ZENHUB_WORKSPACE_NAME")]
+    workspace_name: Option<String>,
+
     /// zen hub api
     #[structopt(long, env = "ZENHUB_API_TOKEN", hide_env_values = true)]
     api_token: String,
 
-    /// zen agent
+    /// zen agent, expected as <name>/<version>
     #[structopt(long, env = "ZENHUB_AGENT", default_value = "webapp/2.45.17")]
     agent: String,
 
+    /// github api root
+    #[structopt(
+        long,
+        env = "GITHUB_API_ROOT",
+        default_value = "https://api.github.com",
+        hidden = true
+    )]
+    github_api_root: String,
+
+    /// crates.io api root, for --check-update
+    #[structopt(
+        long,
+        env = "CRATES_API_ROOT",
+        default_value = "https://crates.io/api/v1",
+        hidden = true
+    )]
+    crates_api_root: String,
+
+    /// check-update - tell when crates.io has a newer version of this tool.
+    /// ZENHUB_NO_UPDATE_CHECK environment variable turns the check off
+    #[structopt(long, global = true)]
+    check_update: bool,
+
+    /// github token, for issue bodies of private repositories
+    #[structopt(long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    github_token: Option<String>,
+
+    /// default-agent - use zenhub/<version of this tool> as zen agent
+    #[structopt(long)]
+    default_agent: bool,
+
+    /// no-dotenv - do not load environment variables from `.env` of the current directory
+    // read in `main`, by parsing the arguments before `.env` is loaded
+    #[structopt(long, global = true)]
+    no_dotenv: bool,
+
+    /// verbose - log API requests to stderr: method, URL, headers with tokens, cookies and
+    /// --header values redacted, and body
+    #[structopt(long, short, global = true)]
+    verbose: bool,
+
+    /// header - extra `Name: Value` header of ZenHub API requests, i.e. for a proxy in front
+    /// of ZenHub. Repeatable; replaces the agent or content type header of the same name.
+    /// Values are redacted by --verbose and not sent on redirects to other hosts
+    #[structopt(long, number_of_values = 1, global = true, parse(try_from_str = parse_header))]
+    header: Vec<(HeaderName, HeaderValue)>,
+
+    /// no-compression - do not ask ZenHub for gzip or brotli compressed responses,
+    /// to see the raw responses while debugging
+    #[structopt(long, global = true)]
+    no_compression: bool,
+
+    /// header-overrides-auth - let --header replace X-Authentication-Token of --api-token
+    #[structopt(long, global = true)]
+    header_overrides_auth: bool,
+
+    /// config file, defaults to `~/.config/zenhub/config.toml`
+    #[structopt(long, env = "ZENHUB_CONFIG", parse(from_os_str))]
+    config: Option<PathBuf>,
+
     /// pipelines to be rendered
-    #[structopt(long, short)]
+    #[structopt(long, short, number_of_values = 1, global = true)]
     pipeline: Vec<String>,
 
     /// eta - sets ETA in hours to the issue
     #[structopt(long, short, default_value = "0.0")]
     estimate: f32,
 
-    /// set issueis pipeline
+    /// set issueis pipeline, by name or by pipeline id
     #[structopt(long, short, default_value = "")]
     set: String,
 
-    /// issue - specify repo and issue # to be affected, colon-separated
+    /// issue - specify repo and issue # to be affected, colon-separated, or GitHub issue URL
     #[structopt(long, short, default_value = "")]
     issue: String,
+
+    /// min-estimate - keep only issues estimated at least that much
+    #[structopt(long, global = true)]
+    min_estimate: Option<f32>,
+
+    /// max-estimate - keep only issues estimated at most that much
+    #[structopt(long, global = true)]
+    max_estimate: Option<f32>,
+
+    /// include-unestimated - keep issues without estimate when estimate range is given
+    #[structopt(long, global = true)]
+    include_unestimated: bool,
+
+    /// only-unestimated - keep only issues without estimate, to find what needs estimating
+    #[structopt(
+        long,
+        global = true,
+        conflicts_with_all = &["min-estimate", "max-estimate", "include-unestimated"]
+    )]
+    only_unestimated: bool,
+
+    /// color - ANSI styling: `auto` for a terminal unless NO_COLOR env variable is set,
+    /// `always` even when piped (i.e. into `less -R`), or `never`
+    #[structopt(
+        long,
+        global = true,
+        default_value = "auto",
+        possible_values = &["auto", "always", "never"]
+    )]
+    color: ColorMode,
+
+    /// no-color - same as `--color never`, wins over `--color always`
+    #[structopt(long, global = true)]
+    no_color: bool,
+
+    /// highlight - emphasize rows assigned to that login, could be repeated; rows are bold,
+    /// or marked with `*` without colors
+    #[structopt(long, number_of_values = 1, global = true)]
+    highlight: Vec<String>,
+
+    /// fixtures-dir - serve API calls from JSON files in that directory
+    #[structopt(long, parse(from_os_str), hidden = true)]
+    fixtures_dir: Option<PathBuf>,
+
+    /// record - save live API responses into --fixtures-dir
+    #[structopt(long, requires = "fixtures-dir", hidden = true)]
+    record: bool,
+
+    /// assignee - whose issues to show: GitHub login, or `me` for the authenticated user.
+    /// Without this flag issues of the authenticated user are shown, same as `me`.
+    /// Only `me` and the default need the user lookup, other logins skip it
+    #[structopt(long, short, global = true)]
+    assignee: Option<String>,
+
+    /// select - keep issues matching the expression, i.e. `estimate > 3 && state == "open"`.
+    /// Fields: estimate, state, title, repo, org, number, pipeline, assignee, assignees, labels,
+    /// milestone, is_epic, is_pr, author, created_at, updated_at, closed_at, url.
+    /// Operators: == != < <= > >= ~= (substring) && || ! and parentheses
+    #[structopt(long, global = true, parse(try_from_str = select::parse))]
+    select: Option<select::Expr>,
+
+    /// title-contains - keep issues whose title contains that text, case-insensitive
+    #[structopt(long, global = true)]
+    title_contains: Option<String>,
+
+    /// title-regex - keep issues whose title matches the regular expression,
+    /// i.e. `'(?i)^(feat|fix)\b'`
+    #[structopt(long, global = true)]
+    title_regex: Option<regex::Regex>,
+
+    /// assignee-file - keep issues assigned to any of the logins of the file, one per line
+    #[structopt(long, global = true, parse(from_os_str), conflicts_with = "assignee")]
+    assignee_file: Option<PathBuf>,
+
+    /// unassigned - keep only issues assigned to nobody, to find work that needs an owner
+    #[structopt(long, global = true, conflicts_with_all = &["assignee", "assignee-file"])]
+    unassigned: bool,
+
+    /// all-assignees - keep issues of anybody, instead of the authenticated user
+    #[structopt(
+        long,
+        global = true,
+        conflicts_with_all = &["assignee", "assignee-file", "unassigned"]
+    )]
+    all_assignees: bool,
+
+    /// logins of --assignee-file
+    #[structopt(skip)]
+    team: Vec<String>,
+
+    /// include-prs - keep pull requests too, told apart from issues by `/pull/` in their URL
+    #[structopt(long, global = true)]
+    include_prs: bool,
+
+    /// issues-only - drop pull requests, which is the default
+    #[structopt(long, global = true, conflicts_with = "include-prs")]
+    issues_only: bool,
+
+    /// label - keep issues having this label, could be repeated
+    #[structopt(long, short, number_of_values = 1, global = true)]
+    label: Vec<String>,
+
+    /// label-match - how multiple labels combine: `any` of them or `all` of them,
+    /// i.e. `-l bug -l p1 --label-match all` keeps only issues labeled both bug and p1
+    #[structopt(long, default_value = "any", possible_values = &["any", "all"], global = true)]
+    label_match: LabelMatch,
+
+    /// label-color - keep issues having any label of this color, as hex `ff0000` or `#FF0000`
+    #[structopt(long, global = true, parse(try_from_str = parse_label_color))]
+    label_color: Option<String>,
+
+    /// milestone - keep issues of the milestone with this title
+    #[structopt(long, short, global = true)]
+    milestone: Option<String>,
+
+    /// state - keep only `open` or `closed` issues
+    #[structopt(long, global = true, possible_values = &["open", "closed"])]
+    state: Option<String>,
+
+    /// filter - named filter of the `[filters.<name>]` section of the config file;
+    /// flags given explicitly win over its values
+    #[structopt(long, global = true)]
+    filter: Option<String>,
+
+    /// created-after - keep issues created at or after that date (`2024-05-01`) or datetime
+    #[structopt(long, global = true, parse(try_from_str = parse_datetime_arg))]
+    created_after: Option<DateTime<Utc>>,
+
+    /// created-before - keep issues created before that date (`2024-06-01`) or datetime
+    #[structopt(long, global = true, parse(try_from_str = parse_datetime_arg))]
+    created_before: Option<DateTime<Utc>>,
+
+    /// active-within - keep issues updated within that long: `3d`, `12h`, `2w` (days by default)
+    #[structopt(long, global = true, parse(try_from_str = parse_age))]
+    active_within: Option<chrono::Duration>,
+
+    /// repo - keep issues of the workspace repository with this name, can be repeated
+    #[structopt(long, number_of_values = 1, global = true)]
+    repo: Vec<String>,
+
+    /// repo-id - keep issues of the repository with this GitHub id, can be repeated.
+    /// Without --repo the workspace repositories are not looked up, except by `graph` to name
+    /// the repositories of several ids
+    #[structopt(long, number_of_values = 1, global = true)]
+    repo_id: Vec<u64>,
+
+    /// output - format of the output: text, json, jsonl, yaml, csv or tsv.
+    /// JSON is pretty-printed to a terminal and compact when piped, see --pretty-json and --compact.
+    /// Issues in jsonl are a line `{"type":"meta","workspace":...,"generated_at":...}`
+    /// followed by a line per issue with `"type":"issue"` added to its fields.
+    /// Issues in csv and tsv have columns pipeline, repo, number, estimate, state, title
+    /// (and url with --with-url), `epic` and `search` list their issues so; `show` has issue,
+    /// pipeline, estimate, epic (and title, state, body with --with-body); `report burndown` has
+    /// pipeline, estimate, closed_estimate, open_estimate, percent_complete; `report velocity`
+    /// has week, closed, estimate; `--assignee-count` has pipeline, assignees, top, top_share,
+    /// concentrated; `validate` has a row of check, issue, pipeline, title per violation,
+    /// `board` rejects csv and tsv;
+    /// tsv has no header row, tabs, line breaks and backslashes in values are escaped as \t, \n, \\.
+    /// With json, estimate, move and milestone print a JSON line with the result of each issue
+    #[structopt(
+        long,
+        short,
+        global = true,
+        default_value = "text",
+        possible_values = &["text", "json", "jsonl", "yaml", "csv", "tsv"]
+    )]
+    output: OutputFormat,
+
+    /// estimate-precision - decimal places of estimates, trailing zeros are trimmed by default
+    #[structopt(long, global = true)]
+    estimate_precision: Option<usize>,
+
+    /// duration-style - how ages and due dates are shown: `short` (`2d 4h`) or `long`
+    /// (`2 days 4 hours`)
+    #[structopt(
+        long,
+        global = true,
+        default_value = "short",
+        possible_values = &["short", "long"]
+    )]
+    duration_style: DurationStyle,
+
+    /// estimate-from-labels - estimate issues that have no ZenHub estimate by their label of
+    /// that prefix, i.e. `sp` for `sp:3`. ZenHub estimate always wins, of several labels the
+    /// first one counts. Derived estimates are marked with `*` and add up to the totals
+    #[structopt(long, global = true)]
+    estimate_from_labels: Option<String>,
+
+    /// round - estimates rounded to `half` or `int` as soon as they are read, so that totals
+    /// add up the values shown; `none` keeps them as is
+    #[structopt(
+        long,
+        global = true,
+        default_value = "none",
+        possible_values = &["none", "half", "int"]
+    )]
+    round: EstimateRound,
+
+    /// delimiter - field separator of `--output csv`, i.e. `;` for spreadsheets of locales
+    /// with decimal comma. Values holding it are quoted
+    #[structopt(
+        long,
+        global = true,
+        default_value = ",",
+        parse(try_from_str = parse_delimiter)
+    )]
+    delimiter: char,
+
+    /// no-header - do not print pipeline headers, or the column header row for csv
+    #[structopt(long, global = true)]
+    no_header: bool,
+
+    /// no-user-banner - do not print the `User` line of the authenticated user before issues,
+    /// warnings on stderr are still printed
+    #[structopt(long, global = true)]
+    no_user_banner: bool,
+
+    /// refresh-user - look up the authenticated user on ZenHub instead of the cache,
+    /// which is kept for a week per token
+    #[structopt(long, global = true)]
+    refresh_user: bool,
+
+    /// compact - print JSON on a single line, also to a terminal. Without --compact and
+    /// --pretty-json JSON is pretty-printed to a terminal and compact when piped
+    #[structopt(long, global = true, conflicts_with = "pretty-json")]
+    compact: bool,
+
+    /// pretty-json - pretty-print JSON also when piped
+    #[structopt(long, global = true)]
+    pretty_json: bool,
+
+    /// sum-field - what is summed up per pipeline: `estimate`, `count` of issues,
+    /// or `label:<prefix>` for numeric labels like `sp:3`
+    #[structopt(long, global = true, default_value = "estimate")]
+    sum_field: SumField,
+
+    /// watch - list issues again every that many seconds, until interrupted
+    #[structopt(long, global = true)]
+    watch: Option<u64>,
+
+    /// delta - with --watch, after the first listing print only new, gone, moved and re-estimated issues
+    #[structopt(long, global = true, requires = "watch")]
+    delta: bool,
+
+    /// stats - add mean, median and max estimate to pipeline headers
+    #[structopt(long, global = true)]
+    stats: bool,
+
+    /// fields-all - print every field of each issue as a labeled block, instead of a row
+    #[structopt(long, global = true)]
+    fields_all: bool,
+
+    /// fields - columns of issue rows, comma-separated: number, title, estimate, state,
+    /// assignee, labels, milestone, pipeline, author, created, updated, url, and `age`
+    /// since creation or `since` the last update
+    #[structopt(long, global = true, use_delimiter = true)]
+    fields: Vec<IssueField>,
+
+    /// view - preset of --fields: `minimal` (number, title), `planning` (number, estimate,
+    /// assignee, title) or `triage` (number, labels, state, updated, title)
+    #[structopt(long, global = true, possible_values = &["minimal", "planning", "triage"])]
+    view: Option<View>,
+
+    /// flatten-epics - leave estimates of epics out of pipeline totals, so that an epic
+    /// listed together with its children is not counted twice; only leaf issues add up
+    #[structopt(long, global = true)]
+    flatten_epics: bool,
+
+    /// pretty - show epics of the listing as trees of their issues
+    #[structopt(long, global = true)]
+    pretty: bool,
+
+    /// with-url - append the GitHub URL of each issue as the last column, for text and csv/tsv
+    #[structopt(long, global = true)]
+    with_url: bool,
+
+    /// histogram - print distribution of estimates after each pipeline
+    #[structopt(long, global = true)]
+    histogram: bool,
+
+    /// assignee-count - instead of issues, print per pipeline how many people have work there
+    /// and who holds the largest share of the estimate, flagged with `!` above 50%.
+    /// Counts the listed issues, use with --all-assignees for the whole team
+    #[structopt(long, global = true)]
+    assignee_count: bool,
+
+    /// show-org - prefix repository with its organization, as org/repo:number
+    #[structopt(long, global = true)]
+    show_org: bool,
+
+    /// resolve-epics - show titles of the parent epics, looked up with one extra request
+    #[structopt(long, global = true)]
+    resolve_epics: bool,
+
+    /// all-pipelines - render every pipeline of the board, in board order
+    #[structopt(long, global = true)]
+    all_pipelines: bool,
+
+    /// sort-pipelines - order of rendered pipelines: `board` order, `name`,
+    /// or heaviest first by total `estimate` or issue `count`
+    #[structopt(
+        long,
+        global = true,
+        default_value = "board",
+        possible_values = &["board", "name", "estimate", "count"]
+    )]
+    sort_pipelines: PipelineSort,
+
+    /// include-closed-pipelines - with --all-pipelines, also render pipelines that look closed
+    /// or archived by their name or description, skipped by default
+    #[structopt(long, global = true)]
+    include_closed_pipelines: bool,
+
+    /// collapse-closed - with --all-pipelines, print pipelines whose issues are all closed
+    /// as a single summary line instead of listing their issues
+    #[structopt(long, global = true)]
+    collapse_closed: bool,
+
+    /// expand-all - list issues of every pipeline, overriding --collapse-closed
+    #[structopt(long, global = true)]
+    expand_all: bool,
+
+    /// exclude-pipeline - skip the pipeline of this name (case-insensitive), could be repeated
+    #[structopt(long, number_of_values = 1, global = true)]
+    exclude_pipeline: Vec<String>,
+
+    /// include-no-pipeline - keep issues returned without pipeline info (i.e. of repos
+    /// not on the board) when filtering by pipeline, listed as "(no pipeline)"
+    #[structopt(long, global = true)]
+    include_no_pipeline: bool,
+
+    /// no-audit - do not append estimate and move changes to ~/.local/state/zenhub/audit.log
+    #[structopt(long, global = true)]
+    no_audit: bool,
+
+    /// yes - do not ask for confirmation before moving issues
+    #[structopt(long, short, global = true)]
+    yes: bool,
+
+    /// concurrency - `--from-file` items processed at once, still within `--rate`
+    #[structopt(long, default_value = "1", global = true)]
+    concurrency: usize,
+
+    /// rate - requests per minute of `--from-file` batches, spaced out to stay under the API limit
+    #[structopt(long, default_value = "100", global = true)]
+    rate: u32,
+
+    /// canonical - sort labels and assignees of issues by name, so that exports of unchanged
+    /// issues are identical across runs
+    #[structopt(long, global = true)]
+    canonical: bool,
+
+    /// show-workspace - start the listing with name and description of the workspace,
+    /// json and yaml put the list into `{"workspace": ..., "pipelines": [...]}`
+    #[structopt(long, global = true)]
+    show_workspace: bool,
+
+    /// workspace of --show-workspace
+    #[structopt(skip)]
+    workspace_header: Option<WorkspaceHeader>,
+
+    /// limiter of batch requests, see --rate
+    #[structopt(skip)]
+    limiter: Option<std::sync::Arc<tokio::sync::Mutex<RateLimiter>>>,
+
+    /// warnings of the listing, collected by the TUI for its status line, as stderr would
+    /// corrupt the board
+    #[structopt(skip)]
+    warnings: Option<std::sync::Arc<std::sync::Mutex<Vec<String>>>>,
+
+    /// max-issues - safety cap on the number of issues kept from the response
+    #[structopt(long, default_value = "10000")]
+    max_issues: usize,
+
+    /// force-update - ask ZenHub to recompute issues instead of serving its cached data:
+    /// slower, but shows changes just made in the web app
+    #[structopt(long, global = true)]
+    force_update: bool,
+
+    /// page-size - repositories fetched per GraphQL request, 1 to 50
+    #[structopt(long, default_value = "50", global = true)]
+    page_size: usize,
+
+    /// api-version - issues endpoint: `v5` workspace issues, `v4` repository boards with
+    /// per-issue GitHub lookups (only with GITHUB_TOKEN, titles are empty without it), or
+    /// `auto` for v5 falling back to v4 when v5 is gone (404, 410) or its response unreadable
+    #[structopt(
+        long,
+        global = true,
+        default_value = "auto",
+        possible_values = &["auto", "v4", "v5"]
+    )]
+    api_version: ApiVersion,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+enum LabelMatch {
+    #[default]
+    Any,
+    All,
+}
+
+impl std::str::FromStr for LabelMatch {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(LabelMatch::Any),
+            "all" => Ok(LabelMatch::All),
+            _ => Err(format!("invalid label match {:?}, expected any or all", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(format!(
+                "invalid color {:?}, expected auto, always or never",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PipelineSort {
+    Board,
+    Name,
+    Estimate,
+    Count,
+}
+
+impl std::str::FromStr for PipelineSort {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "board" => Ok(PipelineSort::Board),
+            "name" => Ok(PipelineSort::Name),
+            "estimate" => Ok(PipelineSort::Estimate),
+            "count" => Ok(PipelineSort::Count),
+            _ => Err(format!(
+                "invalid pipeline sort {:?}, expected board, name, estimate or count",
+                s
+            )),
+        }
+    }
+}
+
+/// Column of `--fields`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IssueField {
+    Number,
+    Title,
+    Estimate,
+    State,
+    Assignee,
+    Labels,
+    Milestone,
+    Pipeline,
+    Author,
+    Created,
+    Updated,
+    Url,
+    Age,
+    Since,
+}
+
+impl std::str::FromStr for IssueField {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "number" => Ok(IssueField::Number),
+            "title" => Ok(IssueField::Title),
+            "estimate" => Ok(IssueField::Estimate),
+            "state" => Ok(IssueField::State),
+            "assignee" => Ok(IssueField::Assignee),
+            "labels" => Ok(IssueField::Labels),
+            "milestone" => Ok(IssueField::Milestone),
+            "pipeline" => Ok(IssueField::Pipeline),
+            "author" => Ok(IssueField::Author),
+            "created" => Ok(IssueField::Created),
+            "updated" => Ok(IssueField::Updated),
+            "url" => Ok(IssueField::Url),
+            "age" => Ok(IssueField::Age),
+            "since" => Ok(IssueField::Since),
+            _ => Err(format!(
+                "invalid field {:?}, expected number, title, estimate, state, assignee, labels, \
+                 milestone, pipeline, author, created, updated, url, age or since",
+                s
+            )),
+        }
+    }
+}
+
+/// Named preset of `--fields`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Minimal,
+    Planning,
+    Triage,
+}
+
+impl View {
+    fn fields(self) -> Vec<IssueField> {
+        use IssueField::*;
+        match self {
+            View::Minimal => vec![Number, Title],
+            View::Planning => vec![Number, Estimate, Assignee, Title],
+            View::Triage => vec![Number, Labels, State, Updated, Title],
+        }
+    }
+}
+
+impl std::str::FromStr for View {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(View::Minimal),
+            "planning" => Ok(View::Planning),
+            "triage" => Ok(View::Triage),
+            _ => Err(format!(
+                "invalid view {:?}, expected minimal, planning or triage",
+                s
+            )),
+        }
+    }
+}
+
+/// Board hygiene check of `validate`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValidateCheck {
+    Unestimated,
+    Unassigned,
+    Stale,
+    NoMilestone,
+}
+
+impl ValidateCheck {
+    const ALL: [ValidateCheck; 4] = [
+        ValidateCheck::Unestimated,
+        ValidateCheck::Unassigned,
+        ValidateCheck::Stale,
+        ValidateCheck::NoMilestone,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ValidateCheck::Unestimated => "unestimated",
+            ValidateCheck::Unassigned => "unassigned",
+            ValidateCheck::Stale => "stale",
+            ValidateCheck::NoMilestone => "no-milestone",
+        }
+    }
+}
+
+impl std::str::FromStr for ValidateCheck {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match ValidateCheck::ALL.iter().find(|c| c.name() == s) {
+            Some(check) => Ok(*check),
+            None => Err(format!(
+                "invalid check {:?}, expected unestimated, unassigned, stale or no-milestone",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DurationStyle {
+    Short,
+    Long,
+}
+
+impl std::str::FromStr for DurationStyle {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" => Ok(DurationStyle::Short),
+            "long" => Ok(DurationStyle::Long),
+            _ => Err(format!(
+                "invalid duration style {:?}, expected short or long",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+enum EstimateRound {
+    #[default]
+    None,
+    Half,
+    Int,
+}
+
+impl EstimateRound {
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            EstimateRound::None => value,
+            EstimateRound::Half => (value * 2.0).round() / 2.0,
+            EstimateRound::Int => value.round(),
+        }
+    }
+}
+
+impl std::str::FromStr for EstimateRound {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(EstimateRound::None),
+            "half" => Ok(EstimateRound::Half),
+            "int" => Ok(EstimateRound::Int),
+            _ => Err(format!(
+                "invalid rounding {:?}, expected none, half or int",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ApiVersion {
+    Auto,
+    V4,
+    V5,
+}
+
+impl std::str::FromStr for ApiVersion {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ApiVersion::Auto),
+            "v4" => Ok(ApiVersion::V4),
+            "v5" => Ok(ApiVersion::V5),
+            _ => Err(format!(
+                "invalid api version {:?}, expected auto, v4 or v5",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+    /// JSON lines: a `meta` line, then an `issue` line per issue
+    Jsonl,
+    Yaml,
+    Csv,
+    Tsv,
+}
+
+impl OutputFormat {
+    /// JSON or JSON lines, for which mutations print JSON results
+    fn is_json(self) -> bool {
+        self == OutputFormat::Json || self == OutputFormat::Jsonl
+    }
+
+    /// CSV or TSV, printed as `delimited_row` rows
+    fn is_delimited(self) -> bool {
+        self == OutputFormat::Csv || self == OutputFormat::Tsv
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(format!(
+                "invalid output {:?}, expected text, json, jsonl, yaml, csv or tsv",
+                s
+            )),
+        }
+    }
+}
+
+/// Numeric value of the issue summed up in the pipeline rollup
+#[derive(Debug, Clone, PartialEq)]
+enum SumField {
+    Estimate,
+    Count,
+    Label(String),
+}
+
+impl SumField {
+    fn name(&self) -> &str {
+        match self {
+            SumField::Estimate => "estimate",
+            SumField::Count => "count",
+            SumField::Label(prefix) => prefix,
+        }
+    }
+
+    /// None when the issue has no value to be summed, i.e. is not estimated
+    fn value(&self, x: &ZenhubIssueInfo) -> Option<f32> {
+        match self {
+            SumField::Estimate => x.estimate,
+            SumField::Count => Some(1.0),
+            SumField::Label(prefix) => label_value(x, prefix),
+        }
+    }
+}
+
+/// Number of the first label like `sp:3` (or `sp=3`, `sp 3`) of the prefix
+fn label_value(x: &ZenhubIssueInfo, prefix: &str) -> Option<f32> {
+    x.labels.iter().find_map(|l| {
+        l.name
+            .strip_prefix(prefix)
+            .and_then(|v| v.trim_start_matches([':', '=']).trim().parse::<f32>().ok())
+    })
+}
+
+impl std::str::FromStr for SumField {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "estimate" => Ok(SumField::Estimate),
+            "count" => Ok(SumField::Count),
+            _ => match s.strip_prefix("label:") {
+                Some(prefix) if !prefix.is_empty() => Ok(SumField::Label(prefix.to_string())),
+                _ => Err(format!(
+                    "invalid sum field {:?}, expected estimate, count or label:<prefix>",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+enum Command {
+    /// move issue to another pipeline
+    Move {
+        /// issue - repo and issue # to be moved, colon-separated, or GitHub issue URL,
+        /// `-` (or left out, with the pipeline only) to move the issues piped to stdin
+        /// (needs --yes, as there is no terminal to ask)
+        issue: Option<String>,
+        /// name of the target pipeline
+        #[structopt(name = "target-pipeline")]
+        target: Option<String>,
+        /// file of `repo:number,pipeline` lines to be moved in bulk
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["issue", "target-pipeline"])]
+        from_file: Option<PathBuf>,
+    },
+    /// move all issues of a pipeline to another one
+    Drain {
+        /// name of the pipeline to be emptied
+        #[structopt(name = "from-pipeline")]
+        from: String,
+        /// name of the target pipeline
+        #[structopt(name = "to-pipeline")]
+        to: String,
+    },
+    /// show pipeline and estimate of the issue
+    Show {
+        /// issue - repo and issue # to be shown, colon-separated, or GitHub issue URL,
+        /// `-` (or left out) to show each of the issues piped to stdin
+        issue: Option<String>,
+        /// with-body - also fetch title and description of the issue from GitHub
+        #[structopt(long)]
+        with_body: bool,
+    },
+    /// find issues by fuzzy matching their titles, best matches first
+    Search {
+        /// words of the title, in order, not necessarily adjacent
+        query: String,
+        /// limit - how many matches to print
+        #[structopt(long, default_value = "20")]
+        limit: usize,
+    },
+    /// blocking dependencies of the issues, as DOT (or node-link JSON with --output json)
+    Graph,
+    /// board hygiene report of the checks in `[validate]` of the config file (all by default),
+    /// exits 1 when any check fails
+    Validate {
+        /// check - run only these: unestimated, unassigned, stale, no-milestone
+        #[structopt(long = "check", use_delimiter = true)]
+        checks: Vec<ValidateCheck>,
+    },
+    /// progress of the epic: its issues per pipeline, closed and open estimate
+    Epic {
+        /// issue - repo and issue # of the epic, colon-separated, or GitHub issue URL
+        issue: String,
+    },
+    /// set estimate of the issue
+    Estimate {
+        /// issue - repo and issue # to be estimated, colon-separated, or GitHub issue URL,
+        /// `-` (or left out, with the value only) to estimate all the issues piped to stdin
+        issue: Option<String>,
+        /// estimate value
+        value: Option<f32>,
+        /// file of `repo:number,estimate` lines to be estimated in bulk
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["issue", "value"])]
+        from_file: Option<PathBuf>,
+    },
+    /// list pipelines of the board
+    Board {
+        /// raw - print the full board as JSON, including pipeline ids and issues
+        #[structopt(long)]
+        raw: bool,
+    },
+    /// list issues of all pipelines, filtered by assignee, label or milestone
+    Issues,
+    /// interactive board: arrows to navigate, `<` `>` to move the issue, `e` to estimate it
+    Tui,
+    /// show the authenticated user
+    User,
+    /// list repositories of the workspace
+    Repos {
+        /// raw - print the workspace as returned by GraphQL: id, name, description, repositories
+        #[structopt(long)]
+        raw: bool,
+    },
+    /// list workspaces of the authenticated user
+    Workspaces,
+    /// authentication helpers
+    Auth {
+        #[structopt(subcommand)]
+        auth: Auth,
+    },
+    /// summary reports over pipelines
+    Report {
+        #[structopt(subcommand)]
+        report: Report,
+    },
+    /// GitHub milestones of issues, needs GITHUB_TOKEN
+    Milestone {
+        #[structopt(subcommand)]
+        milestone: Milestone,
+    },
+    /// post a comment to the GitHub issue, needs GITHUB_TOKEN
+    Comment {
+        /// issue - repo and issue # to comment on, colon-separated, or GitHub issue URL
+        issue: String,
+        /// text of the comment (Markdown), read from stdin when not given or `-`
+        text: Option<String>,
+        /// file to read the text of the comment from
+        #[structopt(long, parse(from_os_str), conflicts_with = "text")]
+        file: Option<PathBuf>,
+    },
+    /// issues in the shape other tools import
+    Export {
+        #[structopt(subcommand)]
+        export: Export,
+    },
+}
+
+#[derive(StructOpt, Debug, Clone)]
+enum Export {
+    /// CSV (or --output json, jsonl, yaml, tsv) for GitHub Projects, columns:
+    /// Title, URL, Status (the pipeline), Estimate (number), Assignees, Labels, Milestone,
+    /// Repository
+    GithubProjects,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+enum Milestone {
+    /// assign an existing milestone of the repository to the issue
+    Set {
+        /// issue - repo and issue # to be updated, colon-separated, or GitHub issue URL
+        issue: String,
+        /// milestone title, case-insensitive
+        title: String,
+    },
+}
+
+#[derive(StructOpt, Debug, Clone)]
+enum Auth {
+    /// check the token: exits 0 when valid, 3 when rejected, 4 on network failure
+    Check,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+enum Report {
+    /// total, closed and remaining estimate per pipeline, for --pipeline or all of the board
+    Burndown,
+    /// estimate of issues closed per week, by their `closed_at`, over the last weeks
+    Velocity {
+        /// weeks - how many weeks back, the current one included
+        #[structopt(long, default_value = "8")]
+        weeks: u32,
+    },
+    /// open issues of the user (or --assignee) in progress and blocked, to paste into a standup
+    Standup,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -87,7 +1074,25 @@ struct ZenhubRepositoriesResponseDataWorkspace {
     id: String,
     name: String,
     description: String,
+    /// all pages of `repositoriesConnection`
+    #[serde(default)]
     repositories: Vec<ZenhubRepository>,
+    #[serde(rename = "repositoriesConnection", default, skip_serializing)]
+    repositories_connection: Option<ZenhubRepositoriesConnection>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubRepositoriesConnection {
+    nodes: Vec<ZenhubRepository>,
+    #[serde(rename = "pageInfo")]
+    page_info: ZenhubPageInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ZenhubPageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -137,6 +1142,9 @@ struct ZenhubPipeline {
     description: Option<String>,
     _id: String,
     issues: Option<Vec<ZenhubIssue>>,
+    /// length of `issues`, added to the board output for dashboards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    issue_count: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -160,6 +1168,21 @@ struct ZenhubIssueInfo {
     user: Option<ZenhubAssignee>,
     issue_number: u64,
     pipeline: Option<ZenhubPipeline>,
+    /// issues this one blocks, with `connections=1`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocking: Vec<ZenhubIssue>,
+    /// issues blocking this one, with `connections=1`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocked_by: Vec<ZenhubIssue>,
+    /// `estimate` comes from a label with `--estimate-from-labels`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    estimate_derived: bool,
+    /// titles of `parent_epics`, filled in for `--resolve-epics`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    epic_titles: Vec<String>,
+    /// GitHub id of the repository, matched by name against the workspace repositories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repo_id: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -169,231 +1192,5867 @@ struct ZenhubBoardResponse {
     pipelines: Vec<ZenhubPipeline>,
 }
 
-#[allow(dead_code)]
-fn zenhub_headers(opt: Opt) -> HeaderMap {
-    let mut headers = HeaderMap::new();
-    headers.insert("X-Authentication-Token", opt.api_token.parse().unwrap());
-    headers.insert("X-Zenhub-Agent", opt.agent.parse().unwrap());
-    headers.insert("Content-Type", "application/json".parse().unwrap());
-    headers
+/// Checks that agent looks like `webapp/2.45.17`: a name and a dotted numeric version
+fn is_valid_agent(agent: &str) -> bool {
+    let mut parts = agent.splitn(2, '/');
+    let name = parts.next().unwrap_or("");
+    let version = parts.next().unwrap_or("");
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !version.is_empty()
+        && version
+            .split('.')
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
 }
 
-#[allow(dead_code)]
-async fn read_user(opt: Opt) -> Result<ZenhubUserResponse, Box<dyn Error>> {
-    let url: String = format!("{}/v1/user", opt.api_root);
-    let response: ZenhubUserResponse = reqwest::Client::new()
-        .get(&url)
-        .headers(zenhub_headers(opt))
-        .send()
-        .await?
-        .json()
-        .await?;
-    Ok(response)
+fn resolve_agent(opt: &mut Opt) {
+    if opt.default_agent {
+        opt.agent = format!("zenhub/{}", env!("CARGO_PKG_VERSION"));
+    } else if !is_valid_agent(&opt.agent) {
+        eprintln!(
+            "Warning: agent {:?} does not look like <name>/<version>, ZenHub may reject it",
+            opt.agent
+        );
+    }
+}
+
+#[derive(Deserialize)]
+struct CratesResponse {
+    #[serde(rename = "crate")]
+    krate: CratesCrate,
+}
+
+#[derive(Deserialize)]
+struct CratesCrate {
+    max_stable_version: Option<String>,
+    max_version: String,
+}
+
+/// Numeric parts of `1.2.3`, pre-release suffix ignored
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Prints a note when crates.io has a newer release, failures are only warned about
+async fn check_update(opt: &Opt) {
+    let url = format!("{}/crates/{}", opt.crates_api_root, env!("CARGO_PKG_NAME"));
+    let latest = async {
+        let resp: CratesResponse = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(5))
+            .build()?
+            .get(&url)
+            .header("User-Agent", concat!("zenhub/", env!("CARGO_PKG_VERSION")))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok::<_, reqwest::Error>(
+            resp.krate
+                .max_stable_version
+                .unwrap_or(resp.krate.max_version),
+        )
+    };
+    match latest.await {
+        Ok(latest) if version_parts(&latest) > version_parts(env!("CARGO_PKG_VERSION")) => {
+            eprintln!(
+                "Note: zenhub {} is available, this is {} (cargo install zenhub)",
+                latest,
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: cannot check for updates: {}", e),
+    }
+}
+
+/// ZenHub answers 400 or 403 to requests with outdated `X-Zenhub-Agent`,
+/// suggests a newer agent rather than leaving a bare status error
+fn agent_hint(opt: &Opt, e: &(dyn Error + 'static)) {
+    let e = match e.downcast_ref::<reqwest::Error>() {
+        Some(e) => e,
+        None => return,
+    };
+    let agent_rejected = matches!(
+        e.status(),
+        Some(reqwest::StatusCode::BAD_REQUEST) | Some(reqwest::StatusCode::FORBIDDEN)
+    );
+    let from_zenhub = e
+        .url()
+        .is_some_and(|url| url.as_str().starts_with(&opt.api_root));
+    if agent_rejected && from_zenhub {
+        eprintln!(
+            "Hint: ZenHub may have rejected the agent {:?}. Try --agent webapp/<newer version>, \
+             as sent in X-Zenhub-Agent header by https://app.zenhub.com",
+            opt.agent
+        );
+    }
+}
+
+/// Parses `--header Name: Value`
+fn parse_header(s: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("invalid header {:?}, expected Name: Value", s))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes())
+        .map_err(|_| format!("invalid header name {:?}", name.trim()))?;
+    let value = HeaderValue::from_str(value.trim())
+        .map_err(|_| format!("invalid value of header {}", name))?;
+    Ok((name, value))
+}
+
+/// Refuses `--header` replacing the API token, unless `--header-overrides-auth` is given
+fn check_headers(opt: &Opt) -> Result<(), Box<dyn Error>> {
+    if !opt.header_overrides_auth
+        && opt
+            .header
+            .iter()
+            .any(|(name, _)| name == "x-authentication-token")
+    {
+        return Err(
+            "--header X-Authentication-Token would replace --api-token, \
+             add --header-overrides-auth if intended"
+                .into(),
+        );
+    }
+    Ok(())
+}
+
+fn zenhub_headers(opt: Opt) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("X-Authentication-Token", opt.api_token.parse().unwrap());
+    headers.insert("X-Zenhub-Agent", opt.agent.parse().unwrap());
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+    for (name, value) in opt.header {
+        headers.insert(name, value);
+    }
+    headers
+}
+
+/// Redirects followed by a ZenHub request, as many as reqwest follows by default
+const MAX_REDIRECTS: usize = 10;
+
+/// ZenHub request, as it is sent again after a redirect
+struct ZenhubRequest {
+    method: reqwest::Method,
+    url: reqwest::Url,
+    headers: HeaderMap,
+    body: Option<String>,
+}
+
+/// Request to be sent after a redirect response, none when the response is not a redirect.
+/// Redirects are followed here rather than by reqwest, which would pass the token on to
+/// any host: headers are kept within the origin, and only the ones that are not credentials
+/// (`X-Authentication-Token`, `Authorization`, cookies, `--header`) go to other origins.
+/// Like browsers do, 301, 302 and 303 turn the request into GET without body
+fn follow_redirect(
+    opt: &Opt,
+    hops: usize,
+    status: reqwest::StatusCode,
+    response_headers: &HeaderMap,
+    request: &mut ZenhubRequest,
+) -> Result<bool, String> {
+    if !status.is_redirection() || status == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(false);
+    }
+    let ZenhubRequest {
+        method,
+        url,
+        headers,
+        body,
+    } = request;
+    let location = response_headers
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| format!("{} from {} without Location", status, url))?;
+    if hops >= MAX_REDIRECTS {
+        return Err(format!("too many redirects, the last to {}", location));
+    }
+    let next = url
+        .join(location)
+        .map_err(|e| format!("invalid redirect from {} to {:?}: {}", url, location, e))?;
+    if next.origin() != url.origin() {
+        for name in [
+            reqwest::header::AUTHORIZATION,
+            reqwest::header::PROXY_AUTHORIZATION,
+            reqwest::header::COOKIE,
+        ] {
+            headers.remove(name);
+        }
+        headers.remove("X-Authentication-Token");
+        for (name, _) in &opt.header {
+            headers.remove(name);
+        }
+    }
+    if opt.verbose {
+        eprintln!("Redirect: {} {} -> {}", status.as_u16(), url, next);
+    }
+    let keeps_method = status == reqwest::StatusCode::TEMPORARY_REDIRECT
+        || status == reqwest::StatusCode::PERMANENT_REDIRECT;
+    if !keeps_method && *method != reqwest::Method::HEAD {
+        *method = reqwest::Method::GET;
+        *body = None;
+    }
+    *url = next;
+    Ok(true)
+}
+
+/// Logs the request for `--verbose`
+fn log_request(
+    opt: &Opt,
+    method: &reqwest::Method,
+    url: &str,
+    headers: &HeaderMap,
+    body: Option<&str>,
+) {
+    if !opt.verbose {
+        return;
+    }
+    for line in request_log_lines(opt, method, url, headers, body) {
+        eprintln!("{}", line);
+    }
+}
+
+/// Lines of `--verbose` for the request. Values of the headers redirects keep within the
+/// origin are redacted: authentication, cookies and every `--header`
+fn request_log_lines(
+    opt: &Opt,
+    method: &reqwest::Method,
+    url: &str,
+    headers: &HeaderMap,
+    body: Option<&str>,
+) -> Vec<String> {
+    let mut lines = vec![format!("> {} {}", method, url)];
+    for (name, value) in headers {
+        let value = if name == "x-authentication-token"
+            || name == reqwest::header::AUTHORIZATION
+            || name == reqwest::header::PROXY_AUTHORIZATION
+            || name == reqwest::header::COOKIE
+            || opt.header.iter().any(|(header, _)| header == name)
+        {
+            "<redacted>"
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        lines.push(format!("> {}: {}", name, value));
+    }
+    if let Some(body) = body {
+        lines.push(format!("> {}", body));
+    }
+    lines
+}
+
+/// Fixture file name for the endpoint, i.e. `v5_workspaces_123_board.json`
+/// or `post_v1_graphql_WorkspaceRepositories.json`. GraphQL calls are told apart by operation name
+fn fixture_name(method: &reqwest::Method, path: &str, body: Option<&str>) -> String {
+    let endpoint = path.split('?').next().unwrap_or("");
+    let mut name = String::new();
+    if method != reqwest::Method::GET {
+        name.push_str(&method.as_str().to_lowercase());
+    }
+    let mut parts: Vec<&str> = endpoint.split('/').filter(|p| !p.is_empty()).collect();
+    if let Some(operation) = body.and_then(|b| b.split("query ").nth(1)) {
+        parts.push(
+            operation
+                .split(|c: char| !c.is_alphanumeric())
+                .next()
+                .unwrap_or(""),
+        );
+    }
+    for part in parts {
+        if !name.is_empty() {
+            name.push('_');
+        }
+        name.extend(part.chars().map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        }));
+    }
+    format!("{}.json", name)
+}
+
+fn fixture_path(
+    opt: &Opt,
+    method: &reqwest::Method,
+    path: &str,
+    body: Option<&str>,
+) -> Option<PathBuf> {
+    opt.fixtures_dir
+        .as_ref()
+        .map(|dir| dir.join(fixture_name(method, path, body)))
+}
+
+/// Copies everything read from the response into the fixture file
+struct RecordingReader<R: Read> {
+    inner: R,
+    file: std::fs::File,
+}
+
+impl<R: Read> Read for RecordingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.file.write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+/// Opens the body of GET request as a reader, so it could be parsed while being downloaded.
+/// Blocking, to be called from `spawn_blocking`
+fn zenhub_reader(
+    opt: &Opt,
+    path: &str,
+) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+    let fixture = fixture_path(opt, &reqwest::Method::GET, path, None);
+    if let Some(fixture) = &fixture {
+        if !opt.record {
+            return Ok(Box::new(std::fs::File::open(fixture).map_err(|e| {
+                format!("cannot read fixture {}: {}", fixture.display(), e)
+            })?));
+        }
+    }
+    let url: String = format!("{}{}", opt.api_root, path);
+    let headers = zenhub_headers(opt.clone());
+    log_request(opt, &reqwest::Method::GET, &url, &headers, None);
+    let client = reqwest::blocking::Client::builder()
+        .gzip(!opt.no_compression)
+        .brotli(!opt.no_compression)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let mut request = ZenhubRequest {
+        method: reqwest::Method::GET,
+        url: reqwest::Url::parse(&url)?,
+        headers,
+        body: None,
+    };
+    let mut hops = 0;
+    let response = loop {
+        let response = client
+            .request(request.method.clone(), request.url.clone())
+            .headers(request.headers.clone())
+            .send()?;
+        if !follow_redirect(
+            opt,
+            hops,
+            response.status(),
+            response.headers(),
+            &mut request,
+        )? {
+            break response.error_for_status()?;
+        }
+        hops += 1;
+    };
+    match fixture {
+        Some(fixture) => Ok(Box::new(RecordingReader {
+            inner: response,
+            file: std::fs::File::create(&fixture)
+                .map_err(|e| format!("cannot write fixture {}: {}", fixture.display(), e))?,
+        })),
+        None => Ok(Box::new(response)),
+    }
+}
+
+/// Token bucket of a single token, refilled at `--rate` per minute, so that requests
+/// of a batch are evenly spaced instead of bursting into 429 responses
+#[derive(Debug)]
+struct RateLimiter {
+    per_second: f64,
+    tokens: f64,
+    last: std::time::Instant,
+    started: std::time::Instant,
+    requests: usize,
+}
+
+impl RateLimiter {
+    fn new(per_minute: u32) -> Self {
+        let now = std::time::Instant::now();
+        RateLimiter {
+            per_second: f64::from(per_minute) / 60.0,
+            tokens: 1.0,
+            last: now,
+            started: now,
+            requests: 0,
+        }
+    }
+
+    async fn acquire(&mut self) {
+        let now = std::time::Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.per_second;
+        self.tokens = (self.tokens + refill).min(1.0);
+        self.last = now;
+        if self.tokens < 1.0 {
+            let wait = (1.0 - self.tokens) / self.per_second;
+            tokio::time::delay_for(std::time::Duration::from_secs_f64(wait)).await;
+            self.tokens = 1.0;
+            self.last = std::time::Instant::now();
+        }
+        self.tokens -= 1.0;
+        self.requests += 1;
+    }
+}
+
+type BatchResult<R> = Result<R, Box<dyn Error>>;
+
+/// Runs `op` over the items of a batch, `--concurrency` of them at a time, with requests
+/// spaced out by the `--rate` limiter when started. `report` gets the outcome of each item
+/// in the order of the items. Returns the number of failed items
+async fn run_batch<'a, T, R, F, Fut, P>(opt: &Opt, items: &'a [T], op: F, mut report: P) -> usize
+where
+    F: Fn(&'a T) -> Fut,
+    Fut: std::future::Future<Output = BatchResult<R>>,
+    P: FnMut(&'a T, BatchResult<R>),
+{
+    let op = &op;
+    let mut outcomes = futures::stream::iter(items)
+        .map(|item| async move { (item, op(item).await) })
+        .buffered(opt.concurrency.max(1));
+    let mut failed = 0;
+    while let Some((item, outcome)) = outcomes.next().await {
+        if outcome.is_err() {
+            failed += 1;
+        }
+        report(item, outcome);
+    }
+    failed
+}
+
+/// Prints `Moved 3, failed 1` summary of the batch (but for `--output json`),
+/// exiting with 1 when any item failed
+async fn finish_batch(opt: &Opt, done: &str, total: usize, failed: usize) {
+    if !opt.output.is_json() {
+        println!("{} {}, failed {}", done, total - failed, failed);
+    }
+    log_rate(opt).await;
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Starts limiting requests to `--rate` for a batch
+fn start_rate_limit(opt: &mut Opt) -> Result<(), Box<dyn Error>> {
+    if opt.rate == 0 {
+        return Err("--rate should be at least 1 request per minute".into());
+    }
+    opt.limiter = Some(std::sync::Arc::new(tokio::sync::Mutex::new(
+        RateLimiter::new(opt.rate),
+    )));
+    Ok(())
+}
+
+/// Logs the effective request rate of the batch for `--verbose`
+async fn log_rate(opt: &Opt) {
+    if let (true, Some(limiter)) = (opt.verbose, &opt.limiter) {
+        let limiter = limiter.lock().await;
+        let minutes = limiter.started.elapsed().as_secs_f64() / 60.0;
+        // the first request goes at once, the rate is of the intervals after it
+        let rate = if minutes > 0.0 {
+            limiter.requests.saturating_sub(1) as f64 / minutes
+        } else {
+            0.0
+        };
+        eprintln!(
+            "Requests: {}, {:.1} per minute (--rate {})",
+            limiter.requests, rate, opt.rate
+        );
+    }
+}
+
+/// Sends request to ZenHub API and returns response body,
+/// replaying it from (or recording it into) `--fixtures-dir` when given
+async fn zenhub_fetch(
+    opt: Opt,
+    method: reqwest::Method,
+    path: &str,
+    body: Option<String>,
+) -> Result<String, Box<dyn Error>> {
+    let fixture = fixture_path(&opt, &method, path, body.as_deref());
+    if let Some(fixture) = &fixture {
+        if !opt.record {
+            return std::fs::read_to_string(fixture)
+                .map_err(|e| format!("cannot read fixture {}: {}", fixture.display(), e).into());
+        }
+    }
+
+    if let Some(limiter) = &opt.limiter {
+        limiter.lock().await.acquire().await;
+    }
+    let url: String = format!("{}{}", opt.api_root, path);
+    let headers = zenhub_headers(opt.clone());
+    log_request(&opt, &method, &url, &headers, body.as_deref());
+    let client = reqwest::Client::builder()
+        .gzip(!opt.no_compression)
+        .brotli(!opt.no_compression)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let mut request = ZenhubRequest {
+        method,
+        url: reqwest::Url::parse(&url)?,
+        headers,
+        body,
+    };
+    let mut hops = 0;
+    let response = loop {
+        let mut builder = client
+            .request(request.method.clone(), request.url.clone())
+            .headers(request.headers.clone());
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+        let response = builder.send().await?;
+        if !follow_redirect(
+            &opt,
+            hops,
+            response.status(),
+            response.headers(),
+            &mut request,
+        )? {
+            break response.error_for_status()?;
+        }
+        hops += 1;
+    };
+    let text = response.text().await?;
+
+    if let Some(fixture) = &fixture {
+        std::fs::write(fixture, &text)
+            .map_err(|e| format!("cannot write fixture {}: {}", fixture.display(), e))?;
+    }
+    Ok(text)
+}
+
+async fn zenhub_get<T: DeserializeOwned>(opt: Opt, path: &str) -> Result<T, Box<dyn Error>> {
+    let text = zenhub_fetch(opt, reqwest::Method::GET, path, None).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// Authenticated user, from the cache when it is fresh (and not `--refresh-user`)
+async fn read_user(opt: Opt) -> Result<ZenhubUserResponse, Box<dyn Error>> {
+    if !opt.refresh_user {
+        if let Some(user) = read_user_cache(&opt) {
+            return Ok(user);
+        }
+    }
+    let user = fetch_user(opt.clone()).await?;
+    write_user_cache(&opt, &user);
+    Ok(user)
+}
+
+async fn fetch_user(opt: Opt) -> Result<ZenhubUserResponse, Box<dyn Error>> {
+    zenhub_get(opt, "/v1/user").await
 }
 
-#[allow(dead_code)]
 async fn read_pipelines(opt: Opt) -> Result<ZenhubBoardResponse, Box<dyn Error>> {
-    let url: String = format!("{}/v5/workspaces/{}/board", opt.api_root, opt.workspace_id);
-    let res = reqwest::Client::new()
-        .get(&url)
-        .headers(zenhub_headers(opt))
+    let path: String = format!("/v5/workspaces/{}/board", opt.workspace_id);
+    zenhub_get(opt, &path).await
+}
+
+/// Prints `Warning: ` to stderr, or collects it in `warnings` when there are
+fn warn(opt: &Opt, message: String) {
+    match &opt.warnings {
+        Some(warnings) => warnings.lock().unwrap().push(message),
+        None => eprintln!("Warning: {}", message),
+    }
+}
+
+/// Neither `assignee` nor `assignees` is set, ZenHub fills in either of them
+fn is_unassigned(x: &ZenhubIssueInfo) -> bool {
+    x.assignee.is_none() && x.assignees.is_empty()
+}
+
+/// Logins of `assignee` and of `assignees`, ZenHub fills in either of them
+fn assignee_logins(x: &ZenhubIssueInfo) -> impl Iterator<Item = &str> {
+    x.assignee
+        .iter()
+        .chain(x.assignees.iter())
+        .map(|a| a.login.as_str())
+}
+
+/// ZenHub lists pull requests along with issues, they only differ by the URL
+fn is_pull_request(x: &ZenhubIssueInfo) -> bool {
+    x.html_url.contains("/pull/")
+}
+
+/// Pipeline ids by pipeline name, there could be several pipelines of the same name
+fn pipeline_ids(board: &ZenhubBoardResponse) -> HashMap<String, Vec<String>> {
+    let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+    for p in &board.pipelines {
+        ids.entry(p.name.clone()).or_default().push(p._id.clone());
+    }
+    ids
+}
+
+/// Resolves pipeline name (or id as is) to the pipeline id on the board
+fn resolve_pipeline_id(
+    ids: &HashMap<String, Vec<String>>,
+    name: &str,
+) -> Result<String, Box<dyn Error>> {
+    if ids.values().any(|v| v.iter().any(|id| id == name)) {
+        return Ok(name.to_string());
+    }
+    match ids.get(name).map(|v| v.as_slice()) {
+        Some([id]) => Ok(id.clone()),
+        Some(many) if !many.is_empty() => Err(format!(
+            "pipeline {:?} is ambiguous, use one of ids: {}",
+            name,
+            many.join(", ")
+        )
+        .into()),
+        _ => Err(format!("pipeline {:?} not found", name).into()),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ZenhubIssuesFilter {
+    by_assignee: Option<String>,
+    /// logins of `--assignee-file`, issue should be assigned to one of them
+    by_assignees: Vec<String>,
+    /// `--unassigned`, neither `assignee` nor `assignees` is set
+    unassigned: bool,
+    by_pipeline_name: Option<String>,
+    /// pipelines to keep issues of, others are dropped while parsing
+    by_pipeline_names: Option<Vec<String>>,
+    /// `--include-no-pipeline`, issues without pipeline pass the pipeline filters
+    include_no_pipeline: bool,
+    min_estimate: Option<f32>,
+    max_estimate: Option<f32>,
+    include_unestimated: bool,
+    only_unestimated: bool,
+    labels: Vec<String>,
+    label_match: LabelMatch,
+    /// lowercase hex without `#`, see `parse_label_color`
+    label_color: Option<String>,
+    by_milestone: Option<String>,
+    by_state: Option<String>,
+    /// `--estimate-from-labels`, applied before the estimate filters
+    estimate_label: Option<String>,
+    /// `--round`, applied before the estimate filters
+    round: EstimateRound,
+    #[serde(skip)]
+    created_after: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    created_before: Option<DateTime<Utc>>,
+    /// `--active-within` back from now
+    #[serde(skip)]
+    updated_after: Option<DateTime<Utc>>,
+    include_prs: bool,
+    only_epics: bool,
+    #[serde(skip)]
+    select: Option<select::Expr>,
+    /// `--title-contains`, lowercase
+    title_contains: Option<String>,
+    #[serde(skip)]
+    title_regex: Option<regex::Regex>,
+}
+
+impl ZenhubIssuesFilter {
+    fn from_opt(opt: &Opt, by_assignee: Option<String>, by_pipeline_name: Option<String>) -> Self {
+        ZenhubIssuesFilter {
+            by_assignee,
+            by_assignees: opt.team.clone(),
+            unassigned: opt.unassigned,
+            by_pipeline_name,
+            by_pipeline_names: None,
+            include_no_pipeline: opt.include_no_pipeline,
+            min_estimate: opt.min_estimate,
+            max_estimate: opt.max_estimate,
+            include_unestimated: opt.include_unestimated,
+            only_unestimated: opt.only_unestimated,
+            labels: opt.label.clone(),
+            label_match: opt.label_match,
+            label_color: opt.label_color.clone(),
+            by_milestone: opt.milestone.clone(),
+            by_state: opt.state.clone(),
+            estimate_label: opt.estimate_from_labels.clone(),
+            round: opt.round,
+            created_after: opt.created_after,
+            created_before: opt.created_before,
+            updated_after: opt.active_within.map(|age| Utc::now() - age),
+            include_prs: opt.include_prs && !opt.issues_only,
+            only_epics: false,
+            select: opt.select.clone(),
+            title_contains: opt.title_contains.as_ref().map(|s| s.to_lowercase()),
+            title_regex: opt.title_regex.clone(),
+        }
+    }
+
+    fn matches(&self, x: &ZenhubIssueInfo) -> bool {
+        self.matches_pipeline(x) && self.matches_except_pipeline(x)
+    }
+
+    /// Fills in the estimate of an unestimated issue from its label (`--estimate-from-labels`),
+    /// then rounds it (`--round`), so that the estimate filters see the value shown
+    fn derive_estimate(&self, x: &mut ZenhubIssueInfo) {
+        if let (None, Some(prefix)) = (x.estimate, &self.estimate_label) {
+            x.estimate = label_value(x, prefix);
+            x.estimate_derived = x.estimate.is_some();
+        }
+        x.estimate = x.estimate.map(|e| self.round.apply(e));
+    }
+
+    fn matches_pipeline(&self, x: &ZenhubIssueInfo) -> bool {
+        if self.by_pipeline_name.is_none() && self.by_pipeline_names.is_none() {
+            return true;
+        }
+        let pipeline = match &x.pipeline {
+            Some(pipeline) => pipeline,
+            None => return self.include_no_pipeline,
+        };
+        if let Some(by_pipeline_name) = &self.by_pipeline_name {
+            if &pipeline.name != by_pipeline_name {
+                return false;
+            }
+        }
+        if let Some(names) = &self.by_pipeline_names {
+            if !names.contains(&pipeline.name) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether the issue is dropped only because it comes without pipeline info
+    fn drops_no_pipeline(&self, x: &ZenhubIssueInfo) -> bool {
+        x.pipeline.is_none() && !self.matches_pipeline(x) && self.matches_except_pipeline(x)
+    }
+
+    fn matches_except_pipeline(&self, x: &ZenhubIssueInfo) -> bool {
+        if !self.include_prs && is_pull_request(x) {
+            return false;
+        }
+        if self.only_epics && !x.is_epic {
+            return false;
+        }
+        if let Some(select) = &self.select {
+            if !select.matches(x) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.title_contains {
+            if !x.title.to_lowercase().contains(text.as_str()) {
+                return false;
+            }
+        }
+        if let Some(re) = &self.title_regex {
+            if !re.is_match(x.title.trim()) {
+                return false;
+            }
+        }
+        if let Some(by_assignee) = &self.by_assignee {
+            if !assignee_logins(x).any(|login| login == by_assignee) {
+                return false;
+            }
+        }
+        if !self.by_assignees.is_empty()
+            && !assignee_logins(x).any(|login| self.by_assignees.iter().any(|a| a == login))
+        {
+            return false;
+        }
+        if self.unassigned && !is_unassigned(x) {
+            return false;
+        }
+        if let Some(by_state) = &self.by_state {
+            if !x.state.eq_ignore_ascii_case(by_state) {
+                return false;
+            }
+        }
+        if let Some(by_milestone) = &self.by_milestone {
+            match &x.milestone {
+                Some(milestone) if milestone.title.eq_ignore_ascii_case(by_milestone) => {}
+                _ => return false,
+            }
+        }
+        if self.created_after.is_some() || self.created_before.is_some() {
+            let created = match parse_zenhub_datetime(&x.created_at) {
+                Some(created) => created,
+                None => {
+                    static WARNED: std::sync::Once = std::sync::Once::new();
+                    WARNED.call_once(|| {
+                        eprintln!(
+                            "Warning: issues with unparseable created_at (i.e. {:?}) are skipped",
+                            x.created_at
+                        )
+                    });
+                    return false;
+                }
+            };
+            if self.created_after.is_some_and(|after| created < after)
+                || self.created_before.is_some_and(|before| created >= before)
+            {
+                return false;
+            }
+        }
+        if let Some(after) = self.updated_after {
+            let updated = x.updated_at.as_deref().and_then(parse_zenhub_datetime);
+            if updated.is_none_or(|updated| updated < after) {
+                return false;
+            }
+        }
+        if !self.labels.is_empty() {
+            let has_label =
+                |name: &String| x.labels.iter().any(|l| l.name.eq_ignore_ascii_case(name));
+            let m = match self.label_match {
+                LabelMatch::Any => self.labels.iter().any(has_label),
+                LabelMatch::All => self.labels.iter().all(has_label),
+            };
+            if !m {
+                return false;
+            }
+        }
+        if let Some(color) = &self.label_color {
+            let has_color = x.labels.iter().any(|l| {
+                l.color
+                    .as_deref()
+                    .is_some_and(|c| c.trim_start_matches('#').eq_ignore_ascii_case(color))
+            });
+            if !has_color {
+                return false;
+            }
+        }
+        if self.only_unestimated && x.estimate.is_some() {
+            return false;
+        }
+        if self.min_estimate.is_some() || self.max_estimate.is_some() {
+            match x.estimate {
+                Some(estimate_val) => {
+                    if let Some(min) = self.min_estimate {
+                        if estimate_val < min {
+                            return false;
+                        }
+                    }
+                    if let Some(max) = self.max_estimate {
+                        if estimate_val > max {
+                            return false;
+                        }
+                    }
+                }
+                None => return self.include_unestimated,
+            }
+        }
+        true
+    }
+}
+
+/// Deserializes JSON array of issues keeping only those matching the filter,
+/// so that non-matching issues are dropped as soon as they are parsed
+struct FilteredIssues {
+    filter: ZenhubIssuesFilter,
+    max_issues: usize,
+}
+
+/// Matching issues, whether some were dropped by `max_issues` cap and how many
+/// were dropped for coming without pipeline info
+struct FilteredIssuesResult {
+    list: Vec<ZenhubIssueInfo>,
+    capped: bool,
+    no_pipeline: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for FilteredIssues {
+    type Value = FilteredIssuesResult;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FilteredIssues {
+    type Value = FilteredIssuesResult;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a list of issues")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut list = vec![];
+        let mut no_pipeline = 0;
+        while let Some(mut x) = seq.next_element::<ZenhubIssueInfo>()? {
+            self.filter.derive_estimate(&mut x);
+            if self.filter.matches(&x) {
+                if list.len() >= self.max_issues {
+                    // the rest of the response is skipped without being kept
+                    while seq.next_element::<IgnoredAny>()?.is_some() {}
+                    return Ok(FilteredIssuesResult {
+                        list,
+                        capped: true,
+                        no_pipeline,
+                    });
+                }
+                list.push(x);
+            } else if self.filter.drops_no_pipeline(&x) {
+                no_pipeline += 1;
+            }
+        }
+        Ok(FilteredIssuesResult {
+            list,
+            capped: false,
+            no_pipeline,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ZenhubPipelineInfo {
+    title: String,
+    list: Vec<ZenhubIssueInfo>,
+    estimate: f32,
+    not_estimated: i32,
+}
+
+/// Sorted and deduplicated repository ids, so that the query string is stable across runs
+fn repo_ids_param(repositories: &[ZenhubRepository]) -> String {
+    let mut ids: Vec<u64> = repositories.iter().map(|x| x.gh_id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+async fn read_issues(
+    opt: Opt,
+    repositories: Vec<ZenhubRepository>,
+    filter: &ZenhubIssuesFilter,
+) -> Result<ZenhubPipelineInfo, Box<dyn Error>> {
+    let res = match opt.api_version {
+        ApiVersion::V5 => read_issues_v5(&opt, &repositories, filter).await?,
+        ApiVersion::V4 => read_issues_v4(&opt, &repositories, filter).await?,
+        ApiVersion::Auto => match read_issues_v5(&opt, &repositories, filter).await {
+            Ok(res) => res,
+            Err(e) if is_v5_unavailable(e.as_ref()) => {
+                warn(
+                    &opt,
+                    format!("v5 issues failed ({}), falling back to v4", e),
+                );
+                read_issues_v4(&opt, &repositories, filter).await?
+            }
+            Err(e) => return Err(e),
+        },
+    };
+    if res.capped {
+        warn(
+            &opt,
+            format!(
+                "more than {} issues matched, the rest is skipped (see --max-issues)",
+                opt.max_issues
+            ),
+        );
+    }
+    if res.no_pipeline > 0 {
+        warn(
+            &opt,
+            format!(
+                "{} issue(s) without pipeline info were dropped by the pipeline filter \
+                 (see --include-no-pipeline)",
+                res.no_pipeline
+            ),
+        );
+    }
+    let mut list = res.list;
+    if opt.canonical {
+        for x in list.iter_mut() {
+            x.labels.sort_by(|a, b| a.name.cmp(&b.name));
+            x.assignees.sort_by(|a, b| a.login.cmp(&b.login));
+        }
+    }
+    fill_repo_ids(&repositories, &mut list);
+    let title = filter
+        .by_pipeline_name
+        .clone()
+        .unwrap_or_else(|| "Issues".to_string());
+    Ok(pipeline_info(&opt, title, list))
+}
+
+/// Sets `repo_id` of the issues by their repository name. Repositories of `--repo-id`
+/// have no name: issues matching none of the named ones are from the only unnamed one
+fn fill_repo_ids(repositories: &[ZenhubRepository], list: &mut [ZenhubIssueInfo]) {
+    let unnamed: Vec<u64> = repositories
+        .iter()
+        .filter(|r| r.name.is_empty())
+        .map(|r| r.gh_id)
+        .collect();
+    for x in list.iter_mut().filter(|x| x.repo_id.is_none()) {
+        x.repo_id = find_named_repo_id(repositories, x).or(match unnamed[..] {
+            [id] => Some(id),
+            _ => None,
+        });
+    }
+}
+
+/// GitHub id of the repository of the issue, by its name and organization, or the owner
+/// and name in its `html_url`
+fn find_named_repo_id(repositories: &[ZenhubRepository], x: &ZenhubIssueInfo) -> Option<u64> {
+    let from_url = parse_issue_url(&x.html_url).ok();
+    repositories
+        .iter()
+        .filter(|r| !r.name.is_empty())
+        .find(|r| match &from_url {
+            Some(url) => r.name == url.repo_name && url.owner_name.as_ref() == Some(&r.owner_name),
+            None => {
+                r.name == x.repo_name
+                    && x.organization_name
+                        .as_ref()
+                        .is_none_or(|org| org == &r.owner_name)
+            }
+        })
+        .map(|r| r.gh_id)
+}
+
+/// Whether `auto` falls back to v4: v5 is not there or answers in another shape. Other
+/// errors (rejected token, network) would fail v4 as well, so they are reported as is
+fn is_v5_unavailable(e: &(dyn Error + 'static)) -> bool {
+    if e.is::<serde_json::Error>() {
+        return true;
+    }
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(e) => {
+            e.is_decode()
+                || e.status().is_some_and(|s| {
+                    s == reqwest::StatusCode::NOT_FOUND || s == reqwest::StatusCode::GONE
+                })
+        }
+        None => false,
+    }
+}
+
+/// Issues of `/v5/workspaces/:workspace_id/issues`, filtered while being parsed
+async fn read_issues_v5(
+    opt: &Opt,
+    repositories: &[ZenhubRepository],
+    filter: &ZenhubIssuesFilter,
+) -> Result<FilteredIssuesResult, Box<dyn Error>> {
+    let ids_str = repo_ids_param(repositories);
+
+    let mut url: String = format!(
+        "/v5/workspaces/{}/issues?repo_ids={}",
+        opt.workspace_id, ids_str
+    );
+
+    url.push_str("&epics=1");
+    url.push_str("&estimates=1");
+    url.push_str("&connections=1");
+    url.push_str(if opt.force_update {
+        "&forceUpdate=1"
+    } else {
+        "&forceUpdate=0"
+    });
+    url.push_str("&pipelines=1");
+    url.push_str("&priorities=1");
+    url.push_str("&releases=1");
+
+    let seed = FilteredIssues {
+        filter: filter.clone(),
+        max_issues: opt.max_issues,
+    };
+    let opt = opt.clone();
+    let res = tokio::task::spawn_blocking(move || {
+        let reader = zenhub_reader(&opt, &url)?;
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let res = seed.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok::<_, Box<dyn Error + Send + Sync>>(res)
+    })
+    .await?
+    .map_err(|e| e as Box<dyn Error>)?;
+    Ok(res)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ZenhubV4Issue {
+    issue_number: u64,
+    estimate: Option<ZenhubEstimateValue>,
+    #[serde(default)]
+    is_epic: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ZenhubV4Pipeline {
+    id: String,
+    name: String,
+    issues: Vec<ZenhubV4Issue>,
+}
+
+/// Board of `/p2/workspaces/:workspace_id/repositories/:repo_id/board`
+#[derive(Serialize, Deserialize, Debug)]
+struct ZenhubV4Board {
+    pipelines: Vec<ZenhubV4Pipeline>,
+}
+
+/// Issues assembled from the board of each repository, with title, state, assignees,
+/// labels and milestone looked up on GitHub issue by issue
+async fn read_issues_v4(
+    opt: &Opt,
+    repositories: &[ZenhubRepository],
+    filter: &ZenhubIssuesFilter,
+) -> Result<FilteredIssuesResult, Box<dyn Error>> {
+    let mut res = FilteredIssuesResult {
+        list: vec![],
+        capped: false,
+        no_pipeline: 0,
+    };
+    // a request per issue would soon run out of the unauthenticated GitHub limit
+    let lookup = opt.github_token.is_some();
+    if !lookup {
+        let message = "no GITHUB_TOKEN, v4 issues have no title, state, assignees or labels";
+        warn(opt, message.to_string());
+    }
+    for repo in repositories {
+        let path = format!(
+            "/p2/workspaces/{}/repositories/{}/board",
+            opt.workspace_id, repo.gh_id
+        );
+        let board: ZenhubV4Board = zenhub_get(opt.clone(), &path).await?;
+        for mut pipeline in board.pipelines {
+            for issue in std::mem::take(&mut pipeline.issues) {
+                let gh = if !lookup || repo.name.is_empty() {
+                    None
+                } else {
+                    read_github_issue(opt, &repo.owner_name, &repo.name, issue.issue_number)
+                        .await
+                        .map_err(|e| {
+                            let message = format!(
+                                "cannot read {}:{} from GitHub: {}",
+                                repo.name, issue.issue_number, e
+                            );
+                            warn(opt, message)
+                        })
+                        .ok()
+                };
+                let mut x = v4_issue_info(repo, &pipeline, issue, gh);
+                filter.derive_estimate(&mut x);
+                if !filter.matches(&x) {
+                    continue;
+                }
+                if res.list.len() >= opt.max_issues {
+                    res.capped = true;
+                    return Ok(res);
+                }
+                res.list.push(x);
+            }
+        }
+    }
+    Ok(res)
+}
+
+fn v4_issue_info(
+    repo: &ZenhubRepository,
+    pipeline: &ZenhubV4Pipeline,
+    issue: ZenhubV4Issue,
+    gh: Option<GithubIssue>,
+) -> ZenhubIssueInfo {
+    let html_url = format!(
+        "https://github.com/{}/{}/issues/{}",
+        repo.owner_name, repo.name, issue.issue_number
+    );
+    let mut x = ZenhubIssueInfo {
+        assignee: None,
+        assignees: vec![],
+        created_at: String::new(),
+        closed_at: None,
+        estimate: issue.estimate.map(|e| e.value),
+        html_url,
+        is_epic: issue.is_epic,
+        labels: vec![],
+        milestone: None,
+        number: Some(issue.issue_number as u32),
+        repo_name: repo.name.clone(),
+        organization_name: Some(repo.owner_name.clone()).filter(|o| !o.is_empty()),
+        parent_epics: vec![],
+        blocking: vec![],
+        blocked_by: vec![],
+        estimate_derived: false,
+        state: "open".to_string(),
+        title: String::new(),
+        updated_at: None,
+        user: None,
+        issue_number: issue.issue_number,
+        pipeline: Some(ZenhubPipeline {
+            name: pipeline.name.clone(),
+            description: None,
+            _id: pipeline.id.clone(),
+            issues: None,
+            issue_count: None,
+        }),
+        epic_titles: vec![],
+        repo_id: Some(repo.gh_id),
+    };
+    if let Some(gh) = gh {
+        x.assignee = gh.assignee;
+        x.assignees = gh.assignees;
+        x.created_at = gh.created_at;
+        x.closed_at = gh.closed_at;
+        if !gh.html_url.is_empty() {
+            x.html_url = gh.html_url;
+        }
+        x.labels = gh.labels;
+        x.milestone = gh.milestone;
+        x.state = gh.state;
+        x.title = gh.title;
+        x.updated_at = gh.updated_at;
+        x.user = gh.user;
+    }
+    x
+}
+
+/// Rolls up estimates (or other `--sum-field`) of the issues list.
+/// With `--flatten-epics` epics are containers of their children: their own value is left
+/// out of the sum and they are not counted as not estimated, only leaf issues add up
+fn pipeline_info(opt: &Opt, title: String, list: Vec<ZenhubIssueInfo>) -> ZenhubPipelineInfo {
+    let mut estimate: f32 = 0.0;
+    let mut not_estimated = 0;
+    for x in list.iter().filter(|x| !(opt.flatten_epics && x.is_epic)) {
+        match opt.sum_field.value(x) {
+            Some(estimate_val) => estimate += estimate_val,
+            None => not_estimated += 1,
+        }
+    }
+    ZenhubPipelineInfo {
+        title,
+        list,
+        estimate,
+        not_estimated,
+    }
+}
+
+/// Splits issues by pipeline, in the order of given pipeline names.
+/// Issues without pipeline come last with `--include-no-pipeline`
+fn group_by_pipeline(
+    opt: &Opt,
+    list: Vec<ZenhubIssueInfo>,
+    names: &[String],
+) -> Vec<ZenhubPipelineInfo> {
+    let mut groups: HashMap<String, Vec<ZenhubIssueInfo>> = HashMap::new();
+    let mut no_pipeline = vec![];
+    for x in list {
+        match &x.pipeline {
+            Some(pipeline) => groups.entry(pipeline.name.clone()).or_default().push(x),
+            None => no_pipeline.push(x),
+        }
+    }
+    let mut res: Vec<ZenhubPipelineInfo> = names
+        .iter()
+        .map(|name| {
+            let list = groups.remove(name).unwrap_or_default();
+            pipeline_info(opt, name.clone(), list)
+        })
+        .collect();
+    if opt.include_no_pipeline && !no_pipeline.is_empty() {
+        res.push(pipeline_info(opt, "(no pipeline)".to_string(), no_pipeline));
+    }
+    res
+}
+
+/// Workspace with its name, description and repositories
+async fn read_workspace(
+    opt: Opt,
+) -> Result<ZenhubRepositoriesResponseDataWorkspace, Box<dyn Error>> {
+    let mut repositories = vec![];
+    let mut after = String::new();
+    loop {
+        let payload = format!(
+            r###"{{"query":"query WorkspaceRepositories {{
+        workspace(id: \"{}\") {{
+            ...space
+        }}
+    }}
+    fragment space on Workspace {{
+        id
+        name
+        description
+        repositoriesConnection(first: {}{}) {{
+            nodes {{
+                ghId
+                name
+                ownerName
+            }}
+            pageInfo {{
+                hasNextPage
+                endCursor
+            }}
+        }}
+    }}
+"}}"###,
+            opt.workspace_id, opt.page_size, after
+        )
+        .replace('\n', "\\n");
+
+        let text = zenhub_fetch(
+            opt.clone(),
+            reqwest::Method::POST,
+            "/v1/graphql",
+            Some(payload),
+        )
+        .await?;
+        let r: ZenhubRepositoriesResponse = serde_json::from_str(&text)?;
+        let mut workspace = r.data.workspace;
+        let page = workspace
+            .repositories_connection
+            .take()
+            .ok_or("workspace has no repositoriesConnection")?;
+        repositories.extend(page.nodes);
+        match page.page_info.end_cursor {
+            Some(cursor) if page.page_info.has_next_page => {
+                after = format!(", after: \\\"{}\\\"", cursor);
+            }
+            _ => {
+                workspace.repositories = repositories;
+                return Ok(workspace);
+            }
+        }
+    }
+}
+
+/// ZenHub allows at most 50 nodes per page
+const MAX_PAGE_SIZE: usize = 50;
+
+fn clamp_page_size(opt: &mut Opt) {
+    let clamped = opt.page_size.clamp(1, MAX_PAGE_SIZE);
+    if clamped != opt.page_size {
+        eprintln!(
+            "Warning: --page-size {} is out of 1..{}, using {}",
+            opt.page_size, MAX_PAGE_SIZE, clamped
+        );
+        opt.page_size = clamped;
+    }
+}
+
+async fn read_repositories(opt: Opt) -> Result<Vec<ZenhubRepository>, Box<dyn Error>> {
+    Ok(read_workspace(opt).await?.repositories)
+}
+
+/// Repositories to list issues of: `--repo` and `--repo-id` united, or all of the workspace
+async fn read_issue_repositories(opt: &Opt) -> Result<Vec<ZenhubRepository>, Box<dyn Error>> {
+    let mut result: Vec<ZenhubRepository> = opt
+        .repo_id
+        .iter()
+        .map(|id| ZenhubRepository {
+            gh_id: *id,
+            name: String::new(),
+            owner_name: String::new(),
+        })
+        .collect();
+    if !result.is_empty() && opt.repo.is_empty() {
+        return Ok(result);
+    }
+    let repositories = read_repositories(opt.clone()).await?;
+    if opt.repo.is_empty() {
+        return Ok(repositories);
+    }
+    for name in &opt.repo {
+        let found: Vec<&ZenhubRepository> = repositories
+            .iter()
+            .filter(|r| r.name.eq_ignore_ascii_case(name))
+            .collect();
+        if found.is_empty() {
+            return Err(format!("repository {:?} is not in the workspace", name).into());
+        }
+        result.extend(found.into_iter().cloned());
+    }
+    Ok(result)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubWorkspace {
+    id: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubWorkspacesNodes {
+    nodes: Vec<ZenhubWorkspace>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ZenhubViewer {
+    search_workspaces: ZenhubWorkspacesNodes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubWorkspacesResponseData {
+    viewer: ZenhubViewer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubWorkspacesResponse {
+    data: ZenhubWorkspacesResponseData,
+}
+
+async fn read_workspaces(opt: Opt) -> Result<Vec<ZenhubWorkspace>, Box<dyn Error>> {
+    let payload = r###"{"query":"query Workspaces {
+        viewer {
+            searchWorkspaces(query: \"\") {
+                nodes {
+                    id
+                    name
+                }
+            }
+        }
+    }
+"}"###
+        .replace('\n', "\\n");
+    let text = zenhub_fetch(opt, reqwest::Method::POST, "/v1/graphql", Some(payload)).await?;
+    let r: ZenhubWorkspacesResponse = serde_json::from_str(&text)?;
+    Ok(r.data.viewer.search_workspaces.nodes)
+}
+
+/// `$XDG_CACHE_HOME/zenhub`, defaults to `~/.cache/zenhub`
+fn cache_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("zenhub")),
+        _ => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("zenhub")),
+    }
+}
+
+fn workspaces_cache_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("workspaces.json"))
+}
+
+const USER_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 3600);
+
+/// Cache file of the user of the token, named by a hash of the API root and the token,
+/// so that the token itself is not written to disk
+fn user_cache_path(opt: &Opt) -> Option<PathBuf> {
+    use std::hash::{Hash, Hasher};
+    // the hasher is not stable across Rust releases, a changed name is only a cache miss
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    opt.api_root.hash(&mut hasher);
+    opt.api_token.hash(&mut hasher);
+    cache_dir().map(|dir| dir.join(format!("user-{:016x}.json", hasher.finish())))
+}
+
+fn read_user_cache(opt: &Opt) -> Option<ZenhubUserResponse> {
+    let path = user_cache_path(opt)?;
+    let age = std::fs::metadata(&path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age > USER_CACHE_TTL {
+        return None;
+    }
+    let user = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+    if opt.verbose {
+        eprintln!("Cached user {}", path.display());
+    }
+    Some(user)
+}
+
+fn write_user_cache(opt: &Opt, user: &ZenhubUserResponse) {
+    if let Some(path) = user_cache_path(opt) {
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, serde_json::to_string(user).unwrap_or_default()));
+        if let Err(e) = written {
+            eprintln!("Warning: cannot write {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn read_workspaces_cache() -> Vec<ZenhubWorkspace> {
+    workspaces_cache_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_workspaces_cache(workspaces: &[ZenhubWorkspace]) {
+    if let Some(path) = workspaces_cache_path() {
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                std::fs::write(&path, serde_json::to_string(workspaces).unwrap_or_default())
+            });
+        if let Err(e) = written {
+            eprintln!("Warning: cannot write {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Ids of the workspaces of that name (case-insensitive)
+fn find_workspace_ids(workspaces: &[ZenhubWorkspace], name: &str) -> Vec<String> {
+    workspaces
+        .iter()
+        .filter(|w| w.name.eq_ignore_ascii_case(name))
+        .map(|w| w.id.clone())
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ZenhubConfig {
+    /// short names of pipelines, `wip = "In Progress / Doing"`
+    #[serde(default)]
+    aliases: HashMap<String, String>,
+    /// named filters for `--filter`
+    #[serde(default)]
+    filters: HashMap<String, FilterPreset>,
+    /// checks of `validate`
+    #[serde(default)]
+    validate: ValidateConfig,
+}
+
+/// `[validate]` of the config file, i.e. `checks = ["unestimated", "stale"]`,
+/// `stale-after = "2w"`, `active-pipelines = ["In Progress", "Review"]`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ValidateConfig {
+    /// checks to run, all when not given
+    checks: Option<Vec<String>>,
+    /// age without updates that makes an open issue stale, 14d by default
+    stale_after: Option<String>,
+    /// pipelines where issues need an assignee, all but the first one by default
+    #[serde(default)]
+    active_pipelines: Vec<String>,
+}
+
+/// Value given as a single string or as a list of them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(s) => vec![s],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+/// `[filters.mybugs]` of the config file, i.e. `label = "bug"`, `assignee = "me"`,
+/// `state = "open"`; each key stands for the flag of that name
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct FilterPreset {
+    label: Option<OneOrMany>,
+    assignee: Option<String>,
+    state: Option<String>,
+    pipeline: Option<OneOrMany>,
+    milestone: Option<String>,
+    min_estimate: Option<f32>,
+    max_estimate: Option<f32>,
+    title_contains: Option<String>,
+    select: Option<String>,
+}
+
+/// Applies `--filter` preset of the config file to the options not given on the command line
+fn apply_filter_preset(opt: &mut Opt) -> Result<(), Box<dyn Error>> {
+    let name = match &opt.filter {
+        Some(name) => name.clone(),
+        None => return Ok(()),
+    };
+    let mut config = read_config(opt)?;
+    let preset = match config.filters.remove(&name) {
+        Some(preset) => preset,
+        None => {
+            let mut names: Vec<&String> = config.filters.keys().collect();
+            names.sort();
+            let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+            return Err(format!(
+                "filter {:?} is not in the config file, defined: {}",
+                name,
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            )
+            .into());
+        }
+    };
+    if let (true, Some(label)) = (opt.label.is_empty(), preset.label) {
+        opt.label = label.into_vec();
+    }
+    let assignee_given = opt.assignee.is_some()
+        || opt.assignee_file.is_some()
+        || opt.unassigned
+        || opt.all_assignees;
+    if !assignee_given {
+        opt.assignee = preset.assignee;
+    }
+    if let Some(state) = preset.state.filter(|_| opt.state.is_none()) {
+        if state != "open" && state != "closed" {
+            return Err(format!(
+                "filter {:?}: invalid state {:?}, expected open or closed",
+                name, state
+            )
+            .into());
+        }
+        opt.state = Some(state);
+    }
+    if let (true, Some(pipeline)) = (opt.pipeline.is_empty(), preset.pipeline) {
+        opt.pipeline = pipeline.into_vec();
+    }
+    opt.milestone = opt.milestone.take().or(preset.milestone);
+    opt.min_estimate = opt.min_estimate.or(preset.min_estimate);
+    opt.max_estimate = opt.max_estimate.or(preset.max_estimate);
+    opt.title_contains = opt.title_contains.take().or(preset.title_contains);
+    if let Some(select) = preset.select.filter(|_| opt.select.is_none()) {
+        opt.select = Some(
+            select::parse(&select)
+                .map_err(|e| format!("filter {:?}: invalid select: {}", name, e))?,
+        );
+    }
+    Ok(())
+}
+
+/// `$XDG_CONFIG_HOME/zenhub/config.toml`, defaults to `~/.config/zenhub/config.toml`
+fn config_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("zenhub").join("config.toml"))
+}
+
+/// Reads `--config` file, or the default one when it exists
+fn read_config(opt: &Opt) -> Result<ZenhubConfig, Box<dyn Error>> {
+    let path = match (&opt.config, config_path()) {
+        (Some(path), _) => path.clone(),
+        (None, Some(path)) if path.exists() => path,
+        _ => return Ok(ZenhubConfig::default()),
+    };
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| format!("cannot parse {}: {}", path.display(), e).into())
+}
+
+/// Expands pipeline aliases of the config file in `--pipeline`, `--exclude-pipeline`,
+/// move targets and drain pipelines, warning about aliases to pipelines that are not on the board
+async fn resolve_aliases(opt: &mut Opt) -> Result<(), Box<dyn Error>> {
+    let config = read_config(opt)?;
+    if config.aliases.is_empty() {
+        return Ok(());
+    }
+    let mut expanded = vec![];
+    let mut expand = |name: &mut String| {
+        if let Some(full) = config.aliases.get(name.as_str()) {
+            *name = full.clone();
+            expanded.push(full.clone());
+        }
+    };
+    opt.pipeline.iter_mut().for_each(&mut expand);
+    opt.exclude_pipeline.iter_mut().for_each(&mut expand);
+    expand(&mut opt.set);
+    match &mut opt.cmd {
+        Some(Command::Move {
+            target: Some(target),
+            ..
+        }) => expand(target),
+        Some(Command::Drain { from, to }) => {
+            expand(from);
+            expand(to);
+        }
+        _ => {}
+    }
+    if expanded.is_empty() {
+        return Ok(());
+    }
+    let board = read_pipelines(opt.clone()).await?;
+    for name in expanded {
+        if !board.pipelines.iter().any(|p| p.name == name) {
+            eprintln!("Warning: alias to {:?} which is not on the board", name);
+        }
+    }
+    Ok(())
+}
+
+/// Reads issue references from stdin, as `-` does, when the issue is left out and stdin is
+/// piped: `... | zenhub move Review`, `... | zenhub estimate 3`, `... | zenhub show`
+fn default_stdin_issue(opt: &mut Opt) {
+    if atty::is(atty::Stream::Stdin) {
+        return;
+    }
+    match &mut opt.cmd {
+        Some(Command::Move {
+            issue: issue @ Some(_),
+            target: target @ None,
+            from_file: None,
+        }) => {
+            *target = issue.replace("-".to_string());
+        }
+        Some(Command::Estimate {
+            issue: Some(issue),
+            value: value @ None,
+            from_file: None,
+        }) => {
+            if let Ok(v) = issue.parse() {
+                *value = Some(v);
+                *issue = "-".to_string();
+            }
+        }
+        Some(Command::Show {
+            issue: issue @ None,
+            ..
+        }) => *issue = Some("-".to_string()),
+        _ => {}
+    }
+}
+
+/// Resolves `--workspace-name` to the workspace id, from the cache when possible
+async fn resolve_workspace(opt: &mut Opt) -> Result<(), Box<dyn Error>> {
+    if let Some(name) = opt.workspace_name.clone() {
+        let mut ids = find_workspace_ids(&read_workspaces_cache(), &name);
+        if ids.is_empty() {
+            let workspaces = read_workspaces(opt.clone()).await?;
+            write_workspaces_cache(&workspaces);
+            ids = find_workspace_ids(&workspaces, &name);
+        }
+        match ids.as_slice() {
+            [id] => opt.workspace_id = id.clone(),
+            [] => return Err(format!("workspace {:?} not found", name).into()),
+            many => {
+                return Err(format!(
+                    "workspace name {:?} is ambiguous, use --workspace-id with one of: {}",
+                    name,
+                    many.join(", ")
+                )
+                .into())
+            }
+        }
+    }
+    let needs_workspace = !matches!(
+        opt.cmd,
+        Some(Command::Workspaces) | Some(Command::User) | Some(Command::Auth { .. })
+    );
+    if needs_workspace && opt.workspace_id.is_empty() {
+        return Err(
+            "workspace is required: --workspace-id, ZENHUB_WORKSPACE_ID or --workspace-name".into(),
+        );
+    }
+    Ok(())
+}
+
+/// Parses datetime fields of ZenHub responses: RFC3339 (`2020-07-30T12:34:56.789Z`),
+/// non-standard offsets (`+0000`), datetimes without zone taken as UTC, and plain dates
+fn parse_zenhub_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for fmt in &["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z"] {
+        if let Ok(dt) = DateTime::parse_from_str(s, fmt) {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+    for fmt in &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(DateTime::from_naive_utc_and_offset(dt, Utc));
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
+}
+
+/// Normalizes `--label-color` to lowercase hex without `#`, as GitHub returns label colors
+fn parse_label_color(s: &str) -> Result<String, String> {
+    let hex = s.trim().trim_start_matches('#');
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hex.to_ascii_lowercase())
+    } else {
+        Err(format!(
+            "invalid color {:?}, expected 6 hex digits like ff0000",
+            s
+        ))
+    }
+}
+
+/// Date or datetime of `--created-after` and `--created-before`, in any of the formats
+/// of `parse_zenhub_datetime`
+fn parse_datetime_arg(s: &str) -> Result<DateTime<Utc>, String> {
+    parse_zenhub_datetime(s)
+        .ok_or_else(|| format!("invalid date {:?}, expected i.e. 2024-05-01 or RFC 3339", s))
+}
+
+/// Age of `--active-within`: number with `h`, `d` or `w` unit, days when the unit is omitted
+fn parse_age(s: &str) -> Result<chrono::Duration, String> {
+    let s = s.trim();
+    let (value, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => s.split_at(at),
+        None => (s, "d"),
+    };
+    let value: i64 = value
+        .parse()
+        .map_err(|_| format!("invalid age {:?}, expected i.e. 3d", s))?;
+    match unit {
+        "h" => Ok(chrono::Duration::hours(value)),
+        "d" => Ok(chrono::Duration::days(value)),
+        "w" => Ok(chrono::Duration::weeks(value)),
+        _ => Err(format!("invalid unit of {:?}, expected h, d or w", s)),
+    }
+}
+
+/// Issue reference in `repo:number` form, or parsed from GitHub issue URL
+#[derive(Debug, Clone)]
+struct IssueRef {
+    owner_name: Option<String>,
+    repo_name: String,
+    issue_number: u64,
+}
+
+impl std::fmt::Display for IssueRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.repo_name, self.issue_number)
+    }
+}
+
+/// Parses `https://github.com/owner/repo/issues/123` (or `/pull/123`)
+fn parse_issue_url(s: &str) -> Result<IssueRef, Box<dyn Error>> {
+    let path = s
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_start_matches("github.com/");
+    let parts: Vec<&str> = path.split(['/', '#', '?']).collect();
+    match parts.as_slice() {
+        [owner, repo, "issues", number, ..] | [owner, repo, "pull", number, ..]
+            if !owner.is_empty() && !repo.is_empty() =>
+        {
+            Ok(IssueRef {
+                owner_name: Some(owner.to_string()),
+                repo_name: repo.to_string(),
+                issue_number: number
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid issue number in {:?}", s))?,
+            })
+        }
+        _ => Err(format!(
+            "invalid issue URL {:?}, expected https://github.com/owner/repo/issues/number",
+            s
+        )
+        .into()),
+    }
+}
+
+fn parse_issue_ref(s: &str) -> Result<IssueRef, Box<dyn Error>> {
+    if s.trim().starts_with("https://") || s.trim().starts_with("http://") {
+        return parse_issue_url(s);
+    }
+    let mut parts = s.trim().rsplitn(2, ':');
+    let number = parts.next().unwrap_or("");
+    let repo_name = parts.next().unwrap_or("");
+    if repo_name.is_empty() {
+        return Err(format!("invalid issue {:?}, expected repo:number", s).into());
+    }
+    let issue_number = number
+        .parse::<u64>()
+        .map_err(|_| format!("invalid issue number in {:?}", s))?;
+    Ok(IssueRef {
+        owner_name: None,
+        repo_name: repo_name.to_string(),
+        issue_number,
+    })
+}
+
+fn find_repo_id(
+    repositories: &[ZenhubRepository],
+    issue_ref: &IssueRef,
+) -> Result<u64, Box<dyn Error>> {
+    find_repo(repositories, issue_ref).map(|r| r.gh_id)
+}
+
+/// Workspace repository of the issue, matching owner too when the reference has it
+fn find_repo<'a>(
+    repositories: &'a [ZenhubRepository],
+    issue_ref: &IssueRef,
+) -> Result<&'a ZenhubRepository, Box<dyn Error>> {
+    let found = repositories.iter().find(|r| {
+        r.name == issue_ref.repo_name
+            && issue_ref
+                .owner_name
+                .as_ref()
+                .is_none_or(|owner| owner.eq_ignore_ascii_case(&r.owner_name))
+    });
+    match (found, &issue_ref.owner_name) {
+        (Some(r), _) => Ok(r),
+        (None, Some(owner)) => Err(format!(
+            "repository {}/{} is not in the workspace",
+            owner, issue_ref.repo_name
+        )
+        .into()),
+        (None, None) => Err(format!(
+            "repository {:?} is not in the workspace",
+            issue_ref.repo_name
+        )
+        .into()),
+    }
+}
+
+async fn move_issue(
+    opt: Opt,
+    repo_id: u64,
+    issue_number: u64,
+    pipeline_id: &str,
+) -> Result<(), Box<dyn Error>> {
+    let path: String = format!(
+        "/p2/workspaces/{}/repositories/{}/issues/{}/moves",
+        opt.workspace_id, repo_id, issue_number
+    );
+    let payload = serde_json::json!({ "pipeline_id": pipeline_id, "position": "top" });
+    zenhub_fetch(opt, reqwest::Method::POST, &path, Some(payload.to_string())).await?;
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubEstimateValue {
+    value: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubIssuePipeline {
+    name: String,
+    pipeline_id: String,
+    workspace_id: Option<String>,
+}
+
+/// Issue data of `/p1/repositories/:repo_id/issues/:issue_number`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubIssueData {
+    estimate: Option<ZenhubEstimateValue>,
+    is_epic: Option<bool>,
+    pipeline: Option<ZenhubIssuePipeline>,
+    #[serde(default)]
+    pipelines: Vec<ZenhubIssuePipeline>,
+}
+
+impl ZenhubIssueData {
+    /// Pipeline of the issue in the given workspace
+    fn pipeline_in(&self, workspace_id: &str) -> Option<&ZenhubIssuePipeline> {
+        self.pipelines
+            .iter()
+            .find(|p| p.workspace_id.as_deref() == Some(workspace_id))
+            .or(self.pipeline.as_ref())
+    }
+}
+
+async fn read_issue_data(
+    opt: Opt,
+    repo_id: u64,
+    issue_number: u64,
+) -> Result<ZenhubIssueData, Box<dyn Error>> {
+    let path: String = format!("/p1/repositories/{}/issues/{}", repo_id, issue_number);
+    zenhub_get(opt, &path).await
+}
+
+/// Network failures and server errors are worth retrying, client errors are not
+fn is_retryable(e: &(dyn Error + 'static)) -> bool {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(e) => e.status().is_none_or(|s| s.is_server_error()),
+        None => false,
+    }
+}
+
+const MOVE_ATTEMPTS: u32 = 3;
+
+/// Moves the issue, retrying on failures. Before each retry the issue is re-fetched,
+/// so a move that was applied but not confirmed is not applied twice.
+/// Returns false when the issue turned out to be in the target pipeline already
+async fn move_issue_with_retry(
+    opt: Opt,
+    repo_id: u64,
+    issue_number: u64,
+    pipeline_id: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let mut attempt = 1;
+    loop {
+        match move_issue(opt.clone(), repo_id, issue_number, pipeline_id).await {
+            Ok(()) => return Ok(true),
+            Err(e) if attempt < MOVE_ATTEMPTS && is_retryable(e.as_ref()) => {
+                eprintln!("Warning: move failed ({}), retrying", e);
+                tokio::time::delay_for(std::time::Duration::from_secs(attempt as u64)).await;
+                let data = read_issue_data(opt.clone(), repo_id, issue_number).await?;
+                if let Some(current) = data.pipeline_in(&opt.workspace_id) {
+                    if current.pipeline_id == pipeline_id {
+                        return Ok(false);
+                    }
+                }
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn set_estimate(
+    opt: Opt,
+    repo_id: u64,
+    issue_number: u64,
+    value: f32,
+) -> Result<(), Box<dyn Error>> {
+    let path: String = format!(
+        "/p1/repositories/{}/issues/{}/estimate",
+        repo_id, issue_number
+    );
+    let payload = serde_json::json!({ "estimate": value });
+    zenhub_fetch(opt, reqwest::Method::PUT, &path, Some(payload.to_string())).await?;
+    Ok(())
+}
+
+/// `$XDG_STATE_HOME/zenhub/audit.log`, defaults to `~/.local/state/zenhub/audit.log`
+fn audit_log_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+    Some(dir.join("zenhub").join("audit.log"))
+}
+
+/// Appends `timestamp, action, issue, old, new` line to the audit log, unless `--no-audit`
+fn audit(opt: &Opt, action: &str, issue_ref: &IssueRef, old: Option<String>, new: &str) {
+    if opt.no_audit {
+        return;
+    }
+    let path = match audit_log_path() {
+        Some(path) => path,
+        None => return,
+    };
+    let line = format!(
+        "{}\t{}\t{}\t{}\t{}\n",
+        Utc::now().to_rfc3339(),
+        action,
+        issue_ref,
+        old.unwrap_or_else(|| "-".to_string()),
+        new
+    );
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+        })
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+    if let Err(e) = written {
+        eprintln!("Warning: cannot write audit log {}: {}", path.display(), e);
+    }
+}
+
+/// Outcome of `estimate`, `move` or `milestone` for `--output json`, one JSON line per issue
+#[derive(Serialize)]
+struct MutationResult {
+    issue: String,
+    action: &'static str,
+    old: serde_json::Value,
+    new: serde_json::Value,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// line of `--from-file`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+}
+
+impl MutationResult {
+    fn ok<O: Serialize, N: Serialize>(action: &'static str, issue: String, old: O, new: N) -> Self {
+        MutationResult {
+            issue,
+            action,
+            old: serde_json::json!(old),
+            new: serde_json::json!(new),
+            status: "ok",
+            error: None,
+            line: None,
+        }
+    }
+
+    fn failed(action: &'static str, issue: String, error: &dyn std::fmt::Display) -> Self {
+        MutationResult {
+            issue,
+            action,
+            old: serde_json::Value::Null,
+            new: serde_json::Value::Null,
+            status: "failed",
+            error: Some(error.to_string()),
+            line: None,
+        }
+    }
+
+    fn at_line(self, line: usize) -> Self {
+        MutationResult {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    /// Fields in declaration order, which a `serde_json::Value` would sort
+    fn print(&self) {
+        println!("{}", serde_json::to_string(self).unwrap_or_default());
+    }
+}
+
+/// Current issue data for the audit log and `--output json`, not fetched with `--no-audit`
+/// unless JSON results need the old value
+async fn audit_prefetch(opt: &Opt, repo_id: u64, issue_number: u64) -> Option<ZenhubIssueData> {
+    if opt.no_audit && !opt.output.is_json() {
+        return None;
+    }
+    read_issue_data(opt.clone(), repo_id, issue_number)
+        .await
+        .ok()
+}
+
+async fn run_estimate(opt: Opt, issue: String, value: f32) -> Result<(), Box<dyn Error>> {
+    let issue_ref = parse_issue_ref(&issue)?;
+    let repositories = read_repositories(opt.clone()).await?;
+    let repo_id = find_repo_id(&repositories, &issue_ref)?;
+    let old = audit_prefetch(&opt, repo_id, issue_ref.issue_number)
+        .await
+        .and_then(|data| data.estimate)
+        .map(|e| e.value);
+    set_estimate(opt.clone(), repo_id, issue_ref.issue_number, value).await?;
+    audit(
+        &opt,
+        "estimate",
+        &issue_ref,
+        old.map(|e| e.to_string()),
+        &value.to_string(),
+    );
+    if opt.output.is_json() {
+        MutationResult::ok("estimate", issue_ref.to_string(), old, value).print();
+    } else {
+        println!("OK\t{} estimate {}", issue_ref, value);
+    }
+    Ok(())
+}
+
+fn parse_estimate_line(line: &str) -> Result<(IssueRef, f32), Box<dyn Error>> {
+    let mut parts = line.splitn(2, ',');
+    let issue = parse_issue_ref(parts.next().unwrap_or(""))?;
+    let value = parts.next().unwrap_or("").trim();
+    if value.is_empty() {
+        return Err("missing estimate, expected repo:number,estimate".into());
+    }
+    let value = value
+        .parse()
+        .map_err(|_| format!("invalid estimate {:?}", value))?;
+    Ok((issue, value))
+}
+
+/// Sets estimates of `repo:number,estimate` lines of `--from-file` or of stdin,
+/// reporting each line like `move --from-file`
+async fn run_estimate_batch(
+    mut opt: Opt,
+    lines: Vec<(usize, String)>,
+) -> Result<(), Box<dyn Error>> {
+    let repositories = read_repositories(opt.clone()).await?;
+    start_rate_limit(&mut opt)?;
+    let json = opt.output.is_json();
+    let report =
+        |(line_no, line): &(usize, String),
+         estimated: BatchResult<(IssueRef, Option<f32>, f32)>| match estimated {
+            Ok((issue_ref, old, value)) if json => {
+                MutationResult::ok("estimate", issue_ref.to_string(), old, value)
+                    .at_line(*line_no)
+                    .print()
+            }
+            Ok((issue_ref, _, value)) => {
+                println!("{}\tOK\t{} estimate {}", line_no, issue_ref, value)
+            }
+            Err(e) if json => MutationResult::failed("estimate", line.clone(), &e)
+                .at_line(*line_no)
+                .print(),
+            Err(e) => println!("{}\tFAILED\t{}\t{}", line_no, line, e),
+        };
+    let (opt_ref, repositories) = (&opt, &repositories);
+    // typed inside, as the lifetime of the item comes from `run_batch`
+    let estimate = |item| async move {
+        let (_, line): &(usize, String) = item;
+        let (issue_ref, value) = parse_estimate_line(line)?;
+        let repo_id = find_repo_id(repositories, &issue_ref)?;
+        let old = audit_prefetch(opt_ref, repo_id, issue_ref.issue_number)
+            .await
+            .and_then(|data| data.estimate)
+            .map(|e| e.value);
+        set_estimate(opt_ref.clone(), repo_id, issue_ref.issue_number, value).await?;
+        audit(
+            opt_ref,
+            "estimate",
+            &issue_ref,
+            old.map(|e| e.to_string()),
+            &value.to_string(),
+        );
+        Ok((issue_ref, old, value))
+    };
+    let failed = run_batch(&opt, &lines, estimate, report).await;
+    finish_batch(&opt, "Estimated", lines.len(), failed).await;
+    Ok(())
+}
+
+/// Issue of `/repos/:owner/:repo/issues/:issue_number` of GitHub API
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct GithubIssue {
+    title: String,
+    state: String,
+    body: Option<String>,
+    #[serde(default)]
+    html_url: String,
+    assignee: Option<ZenhubAssignee>,
+    #[serde(default)]
+    assignees: Vec<ZenhubAssignee>,
+    #[serde(default)]
+    labels: Vec<ZenhubLabel>,
+    milestone: Option<ZenhubMilestone>,
+    #[serde(default)]
+    created_at: String,
+    closed_at: Option<String>,
+    updated_at: Option<String>,
+    user: Option<ZenhubAssignee>,
+}
+
+/// Comment of `/repos/:owner/:repo/issues/:issue_number/comments` of GitHub API
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct GithubComment {
+    id: u64,
+    #[serde(default)]
+    html_url: String,
+}
+
+async fn read_github_issue(
+    opt: &Opt,
+    owner: &str,
+    repo: &str,
+    issue_number: u64,
+) -> Result<GithubIssue, Box<dyn Error>> {
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}",
+        opt.github_api_root, owner, repo, issue_number
+    );
+    let request = github_request(opt, reqwest::Method::GET, &url);
+    Ok(request.send().await?.error_for_status()?.json().await?)
+}
+
+fn github_request(opt: &Opt, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+    let mut request = reqwest::Client::new()
+        .request(method, url)
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", concat!("zenhub/", env!("CARGO_PKG_VERSION")));
+    if let Some(token) = &opt.github_token {
+        request = request.header("Authorization", format!("token {}", token));
+    }
+    request
+}
+
+/// Open and closed milestones of the repository
+async fn read_github_milestones(
+    opt: &Opt,
+    owner: &str,
+    repo: &str,
+) -> Result<Vec<ZenhubMilestone>, Box<dyn Error>> {
+    let url = format!(
+        "{}/repos/{}/{}/milestones?state=all&per_page=100",
+        opt.github_api_root, owner, repo
+    );
+    let request = github_request(opt, reqwest::Method::GET, &url);
+    Ok(request.send().await?.error_for_status()?.json().await?)
+}
+
+/// Fails before any lookup when GitHub would reject the change without a token
+fn require_github_token(opt: &Opt, action: &str) -> Result<(), Box<dyn Error>> {
+    match opt.github_token {
+        Some(_) => Ok(()),
+        None => Err(format!("GITHUB_TOKEN (or --github-token) is required to {}", action).into()),
+    }
+}
+
+async fn run_milestone_set(opt: Opt, issue: String, title: String) -> Result<(), Box<dyn Error>> {
+    let issue_ref = parse_issue_ref(&issue)?;
+    require_github_token(&opt, "set milestones")?;
+    let repositories = read_repositories(opt.clone()).await?;
+    let repo = find_repo(&repositories, &issue_ref)?;
+    let milestones = read_github_milestones(&opt, &repo.owner_name, &repo.name).await?;
+    let milestone = match milestones
+        .iter()
+        .find(|m| m.title.eq_ignore_ascii_case(&title))
+    {
+        Some(milestone) => milestone,
+        None => {
+            let titles: Vec<&str> = milestones.iter().map(|m| m.title.as_str()).collect();
+            return Err(format!(
+                "milestone {:?} not found in {}/{}, available: {}",
+                title,
+                repo.owner_name,
+                repo.name,
+                if titles.is_empty() {
+                    "none".to_string()
+                } else {
+                    titles.join(", ")
+                }
+            )
+            .into());
+        }
+    };
+    let old = if opt.no_audit && !opt.output.is_json() {
+        None
+    } else {
+        read_github_issue(&opt, &repo.owner_name, &repo.name, issue_ref.issue_number)
+            .await
+            .ok()
+            .and_then(|gh| gh.milestone)
+            .map(|m| m.title)
+    };
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}",
+        opt.github_api_root, repo.owner_name, repo.name, issue_ref.issue_number
+    );
+    github_request(&opt, reqwest::Method::PATCH, &url)
+        .json(&serde_json::json!({ "milestone": milestone.number }))
+        .send()
+        .await?
+        .error_for_status()?;
+    audit(&opt, "milestone", &issue_ref, old.clone(), &milestone.title);
+    if opt.output.is_json() {
+        MutationResult::ok("milestone", issue_ref.to_string(), old, &milestone.title).print();
+    } else {
+        println!("OK\t{} milestone {}", issue_ref, milestone.title);
+    }
+    Ok(())
+}
+
+/// Text of `comment`: the argument, `--file` or stdin
+fn comment_text(text: Option<String>, file: Option<PathBuf>) -> Result<String, Box<dyn Error>> {
+    let text = match (text, file) {
+        (Some(text), _) if text != "-" => text,
+        (_, Some(path)) => std::fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?,
+        _ => {
+            if atty::is(atty::Stream::Stdin) {
+                return Err(
+                    "no text of the comment, give it as argument, --file or on stdin".into(),
+                );
+            }
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("text of the comment is empty".into());
+    }
+    Ok(text)
+}
+
+async fn run_comment(
+    opt: Opt,
+    issue: String,
+    text: Option<String>,
+    file: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let issue_ref = parse_issue_ref(&issue)?;
+    let text = comment_text(text, file)?;
+    require_github_token(&opt, "post comments")?;
+    let repositories = read_repositories(opt.clone()).await?;
+    let repo = find_repo(&repositories, &issue_ref)?;
+    let url = format!(
+        "{}/repos/{}/{}/issues/{}/comments",
+        opt.github_api_root, repo.owner_name, repo.name, issue_ref.issue_number
+    );
+    let comment: GithubComment = github_request(&opt, reqwest::Method::POST, &url)
+        .json(&serde_json::json!({ "body": text }))
         .send()
         .await?
-        .json()
-        .await?;
-    Ok(res)
+        .error_for_status()?
+        .json()
+        .await?;
+    audit(&opt, "comment", &issue_ref, None, &comment.html_url);
+    if opt.output.is_json() {
+        MutationResult::ok("comment", issue_ref.to_string(), None::<String>, &text).print();
+    } else {
+        println!("OK\t{} comment {}", issue_ref, comment.html_url);
+    }
+    Ok(())
+}
+
+const BODY_PREVIEW_LINES: usize = 10;
+const BODY_PREVIEW_CHARS: usize = 800;
+
+/// First lines of the issue description, marked with `...` when cut
+fn body_preview(body: &str) -> String {
+    let body = body.trim();
+    let mut preview: String = body
+        .lines()
+        .take(BODY_PREVIEW_LINES)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .chars()
+        .take(BODY_PREVIEW_CHARS)
+        .collect();
+    if preview.len() < body.replace("\r\n", "\n").len() {
+        preview.push_str("\n...");
+    }
+    preview
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubShow {
+    issue: String,
+    pipeline: Option<String>,
+    estimate: Option<f32>,
+    is_epic: bool,
+    title: Option<String>,
+    state: Option<String>,
+    body: Option<String>,
+}
+
+async fn run_show(opt: Opt, issue: String, with_body: bool) -> Result<(), Box<dyn Error>> {
+    let issue_ref = parse_issue_ref(&issue)?;
+    let repositories = read_repositories(opt.clone()).await?;
+    let repo = find_repo(&repositories, &issue_ref)?;
+    let data = read_issue_data(opt.clone(), repo.gh_id, issue_ref.issue_number).await?;
+    let mut show = ZenhubShow {
+        issue: issue_ref.to_string(),
+        pipeline: data.pipeline_in(&opt.workspace_id).map(|p| p.name.clone()),
+        estimate: data.estimate.as_ref().map(|e| e.value),
+        is_epic: data.is_epic.unwrap_or(false),
+        title: None,
+        state: None,
+        body: None,
+    };
+    if with_body {
+        let gh = read_github_issue(&opt, &repo.owner_name, &repo.name, issue_ref.issue_number)
+            .await
+            .map_err(|e| format!("cannot read {} from GitHub: {}", issue_ref, e))?;
+        show.title = Some(gh.title);
+        show.state = Some(gh.state);
+        show.body = gh.body.filter(|b| !b.trim().is_empty());
+    }
+    if opt.output.is_delimited() {
+        let mut header = vec!["issue", "pipeline", "estimate", "epic"];
+        let estimate_str = show
+            .estimate
+            .map(|e| format_estimate(&opt, e))
+            .unwrap_or_default();
+        let mut row = vec![
+            show.issue.as_str(),
+            show.pipeline.as_deref().unwrap_or(""),
+            &estimate_str,
+            if show.is_epic { "yes" } else { "no" },
+        ];
+        if with_body {
+            header.extend(["title", "state", "body"]);
+            row.extend([
+                show.title.as_deref().unwrap_or("").trim(),
+                show.state.as_deref().unwrap_or(""),
+                show.body.as_deref().unwrap_or(""),
+            ]);
+        }
+        if opt.output == OutputFormat::Csv && !opt.no_header {
+            println!("{}", delimited_row(&opt, &header));
+        }
+        println!("{}", delimited_row(&opt, &row));
+        return Ok(());
+    }
+    if opt.output != OutputFormat::Text {
+        return print_data(&opt, &show);
+    }
+    let estimate_str = show
+        .estimate
+        .map(|e| format!("{}h", format_estimate(&opt, e)))
+        .unwrap_or_else(|| "not estimated".to_string());
+    println!(
+        "{}\t{}\t{}{}",
+        show.issue,
+        show.pipeline.as_deref().unwrap_or("(no pipeline)"),
+        estimate_str,
+        if show.is_epic { "\tepic" } else { "" }
+    );
+    if with_body {
+        let color = use_color(&opt);
+        println!(
+            "{}\t{}",
+            paint_state(color, show.state.as_deref().unwrap_or("")),
+            show.title.as_deref().unwrap_or("").trim()
+        );
+        match &show.body {
+            Some(body) => println!("\n{}", body_preview(body)),
+            None => println!("\n(no description)"),
+        }
+    }
+    Ok(())
+}
+
+/// Issues of the whole workspace ranked by fuzzy match of the title, unless `--assignee`
+/// or the other filters narrow them down
+async fn run_search(opt: Opt, query: String, limit: usize) -> Result<(), Box<dyn Error>> {
+    let username = match opt.assignee {
+        Some(_) => Some(resolve_assignee(&opt).await?),
+        None => None,
+    };
+    let repositories = read_issue_repositories(&opt).await?;
+    let filter = ZenhubIssuesFilter::from_opt(&opt, username, None);
+    let matcher = SkimMatcherV2::default();
+    let mut found: Vec<(i64, ZenhubIssueInfo)> = read_issues(opt.clone(), repositories, &filter)
+        .await?
+        .list
+        .into_iter()
+        .filter_map(|x| Some((matcher.fuzzy_match(x.title.trim(), &query)?, x)))
+        .collect();
+    found.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    found.truncate(limit);
+    if opt.output.is_delimited() {
+        let list = found.into_iter().map(|(_, x)| x).collect();
+        display_csv(&opt, &[pipeline_info(&opt, String::new(), list)]);
+        return Ok(());
+    }
+    if opt.output != OutputFormat::Text {
+        let list: Vec<&ZenhubIssueInfo> = found.iter().map(|(_, x)| x).collect();
+        return print_data(&opt, &list);
+    }
+    if found.is_empty() {
+        eprintln!("No issues match {:?}", query);
+    }
+    let color = use_color(&opt);
+    for (_, x) in &found {
+        println!(
+            "{}:{}\t{}\t{}\t{}",
+            repo_display(&opt, x),
+            x.issue_number,
+            paint_state(color, &x.state),
+            x.pipeline
+                .as_ref()
+                .map_or("(no pipeline)", |p| p.name.as_str()),
+            x.title.trim()
+        );
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubEpic {
+    issue: String,
+    title: Option<String>,
+    children: usize,
+    estimate: f32,
+    closed_estimate: f32,
+    open_estimate: f32,
+    /// none when no child is estimated
+    percent_complete: Option<f32>,
+    /// child issues per pipeline, in the order of the board
+    pipelines: Vec<(String, usize)>,
+    issues: Vec<ZenhubIssueInfo>,
+}
+
+/// Issues having the epic among `parent_epics`, with closed and open estimate summed up
+/// like `report burndown` does
+async fn run_epic(opt: Opt, issue: String) -> Result<(), Box<dyn Error>> {
+    let issue_ref = parse_issue_ref(&issue)?;
+    let repositories = read_repositories(opt.clone()).await?;
+    let repo_id = find_repo_id(&repositories, &issue_ref)?;
+    let data = read_issue_data(opt.clone(), repo_id, issue_ref.issue_number).await?;
+    if !data.is_epic.unwrap_or(false) {
+        return Err(format!("{} is not an epic", issue_ref).into());
+    }
+    let board = read_pipelines(opt.clone()).await?;
+    let filter = ZenhubIssuesFilter {
+        include_prs: true,
+        ..Default::default()
+    };
+    let all = read_issues(opt.clone(), read_issue_repositories(&opt).await?, &filter).await?;
+    let mut title = None;
+    let mut issues = vec![];
+    for x in all.list {
+        // issues of several `--repo-id` have no repository id, only their name to go by
+        let same_repo = x
+            .repo_id
+            .map_or(x.repo_name == issue_ref.repo_name, |id| id == repo_id);
+        if same_repo && x.issue_number == issue_ref.issue_number {
+            title = Some(x.title.trim().to_string());
+        } else if x
+            .parent_epics
+            .iter()
+            .any(|e| e.repo_id == repo_id && e.issue_number == issue_ref.issue_number)
+        {
+            issues.push(x);
+        }
+    }
+    if opt.output.is_delimited() {
+        // the child issues, one row each
+        display_csv(&opt, &[pipeline_info(&opt, String::new(), issues)]);
+        return Ok(());
+    }
+    let progress = ZenhubBurndown::new(&opt.sum_field, issue_ref.to_string(), &issues);
+    let pipelines = board
+        .pipelines
+        .iter()
+        .map(|p| {
+            let count = issues
+                .iter()
+                .filter(|x| x.pipeline.as_ref().is_some_and(|xp| xp.name == p.name))
+                .count();
+            (p.name.clone(), count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    let epic = ZenhubEpic {
+        issue: issue_ref.to_string(),
+        title,
+        children: issues.len(),
+        estimate: progress.estimate,
+        closed_estimate: progress.closed_estimate,
+        open_estimate: progress.open_estimate,
+        percent_complete: progress.percent_complete,
+        pipelines,
+        issues,
+    };
+    if opt.output != OutputFormat::Text {
+        return print_data(&opt, &epic);
+    }
+    println!(
+        "{}\t{}",
+        epic.issue,
+        epic.title.as_deref().unwrap_or("(unknown title)")
+    );
+    match epic.percent_complete {
+        Some(pct) => println!(
+            "issues: {}, {}: {}, closed: {} ({:.0}%), open: {} ({:.0}%)",
+            epic.children,
+            opt.sum_field.name(),
+            format_estimate(&opt, epic.estimate),
+            format_estimate(&opt, epic.closed_estimate),
+            pct,
+            format_estimate(&opt, epic.open_estimate),
+            100.0 - pct
+        ),
+        None => println!("issues: {}, none estimated", epic.children),
+    }
+    for (name, count) in &epic.pipelines {
+        println!("{}\t{}", name, count);
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubGraphNode {
+    id: String,
+    /// none for issues that are only known from the connections of listed ones
+    title: Option<String>,
+    state: Option<String>,
+    pipeline: Option<String>,
+    estimate: Option<f32>,
+}
+
+/// Edge from the blocking issue to the blocked one
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubGraphLink {
+    source: String,
+    target: String,
+}
+
+/// Node-link graph of blocking dependencies, as d3 takes it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubGraph {
+    nodes: Vec<ZenhubGraphNode>,
+    links: Vec<ZenhubGraphLink>,
+}
+
+/// Blocking dependencies between the listed issues and their connections. Edges are
+/// collected from both ends, so an edge known by both issues is there once
+fn blocking_graph(repositories: &[ZenhubRepository], list: &[ZenhubIssueInfo]) -> ZenhubGraph {
+    // repositories of `--repo-id` have no name, their issues do
+    let names: HashMap<u64, &str> = list
+        .iter()
+        .filter_map(|x| Some((x.repo_id?, x.repo_name.as_str())))
+        .chain(
+            repositories
+                .iter()
+                .filter(|r| !r.name.is_empty())
+                .map(|r| (r.gh_id, r.name.as_str())),
+        )
+        .collect();
+    let node_id = |(repo_id, number): (u64, u64)| match names.get(&repo_id) {
+        Some(name) => format!("{}:{}", name, number),
+        None => format!("{}:{}", repo_id, number),
+    };
+    let mut edges: BTreeSet<((u64, u64), (u64, u64))> = BTreeSet::new();
+    let mut listed: HashMap<(u64, u64), &ZenhubIssueInfo> = HashMap::new();
+    for x in list {
+        let repo_id = match x.repo_id {
+            Some(repo_id) => repo_id,
+            None => continue,
+        };
+        let key = (repo_id, x.issue_number);
+        listed.insert(key, x);
+        for b in &x.blocking {
+            edges.insert((key, (b.repo_id, b.issue_number)));
+        }
+        for b in &x.blocked_by {
+            edges.insert(((b.repo_id, b.issue_number), key));
+        }
+    }
+    let mut keys: Vec<(u64, u64)> = list
+        .iter()
+        .filter_map(|x| Some((x.repo_id?, x.issue_number)))
+        .collect();
+    for (from, to) in &edges {
+        for key in [from, to] {
+            if !listed.contains_key(key) && !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+    }
+    let nodes = keys
+        .iter()
+        .map(|key| {
+            let x = listed.get(key);
+            ZenhubGraphNode {
+                id: node_id(*key),
+                title: x.map(|x| x.title.trim().to_string()),
+                state: x.map(|x| x.state.clone()),
+                pipeline: x.and_then(|x| x.pipeline.as_ref().map(|p| p.name.clone())),
+                estimate: x.and_then(|x| x.estimate),
+            }
+        })
+        .collect();
+    let links = edges
+        .iter()
+        .map(|(from, to)| ZenhubGraphLink {
+            source: node_id(*from),
+            target: node_id(*to),
+        })
+        .collect();
+    ZenhubGraph { nodes, links }
+}
+
+/// Quoted string of the DOT language, line breaks become `\n` of Graphviz labels
+fn dot_string(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Sets `repo_id` of the issues left without one, of several `--repo-id`, which the graph
+/// keys its nodes on. Only the graph names these repositories by the workspace
+async fn graph_repo_ids(
+    opt: &Opt,
+    repositories: &[ZenhubRepository],
+    list: &mut [ZenhubIssueInfo],
+) {
+    if list.iter().all(|x| x.repo_id.is_some()) {
+        return;
+    }
+    let named = match read_repositories(opt.clone()).await {
+        Ok(named) => named,
+        Err(e) => {
+            eprintln!("Warning: cannot name repositories of --repo-id: {}", e);
+            return;
+        }
+    };
+    let unnamed: Vec<u64> = repositories
+        .iter()
+        .filter(|r| r.name.is_empty())
+        .map(|r| r.gh_id)
+        .collect();
+    for x in list.iter_mut().filter(|x| x.repo_id.is_none()) {
+        x.repo_id = find_named_repo_id(&named, x).filter(|id| unnamed.contains(id));
+    }
+}
+
+/// Blocking dependencies of the issues as DOT for Graphviz, or node-link JSON with `--output`
+async fn run_graph(mut opt: Opt) -> Result<(), Box<dyn Error>> {
+    // DOT goes to `dot` as it is, without the user banner
+    opt.no_user_banner = true;
+    let username = filter_assignee(&opt).await?;
+    let repositories = read_issue_repositories(&opt).await?;
+    let filter = ZenhubIssuesFilter::from_opt(&opt, username, None);
+    let mut issues = read_issues(opt.clone(), repositories.clone(), &filter).await?;
+    graph_repo_ids(&opt, &repositories, &mut issues.list).await;
+    let graph = blocking_graph(&repositories, &issues.list);
+    match opt.output {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml => {
+            return print_data(&opt, &graph)
+        }
+        OutputFormat::Text | OutputFormat::Csv | OutputFormat::Tsv => {}
+    }
+    println!("digraph blocking {{");
+    for node in &graph.nodes {
+        let label = match &node.title {
+            Some(title) => format!("{}\n{}", node.id, title),
+            None => node.id.clone(),
+        };
+        let style = if node.state.as_deref() == Some("closed") {
+            ", style=dashed"
+        } else {
+            ""
+        };
+        println!(
+            "    {} [label={}{}];",
+            dot_string(&node.id),
+            dot_string(&label),
+            style
+        );
+    }
+    for link in &graph.links {
+        println!(
+            "    {} -> {};",
+            dot_string(&link.source),
+            dot_string(&link.target)
+        );
+    }
+    println!("}}");
+    Ok(())
+}
+
+/// Numbered lines, skipping blanks and `#` comments
+fn batch_lines(content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line.trim().to_string()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Reads lines of a `--from-file` batch, skipping blanks and `#` comments
+fn read_batch_file(path: &PathBuf) -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    Ok(batch_lines(&content))
+}
+
+/// Issue references piped to stdin for the `-` issue: the first word of each line,
+/// so that rows of `zenhub issues` or of `grep` pass as they are
+fn read_stdin_refs() -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+    if atty::is(atty::Stream::Stdin) {
+        return Err("`-` reads issue references from stdin, which is a terminal".into());
+    }
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content)?;
+    let refs: Vec<(usize, String)> = batch_lines(&content)
+        .into_iter()
+        .filter_map(|(n, line)| Some((n, line.split_whitespace().next()?.to_string())))
+        .collect();
+    if refs.is_empty() {
+        return Err("no issue references on stdin".into());
+    }
+    Ok(refs)
+}
+
+fn parse_move_line(line: &str) -> Result<(IssueRef, String), Box<dyn Error>> {
+    let mut parts = line.splitn(2, ',');
+    let issue = parse_issue_ref(parts.next().unwrap_or(""))?;
+    let pipeline = parts.next().unwrap_or("").trim();
+    if pipeline.is_empty() {
+        return Err("missing pipeline, expected repo:number,pipeline".into());
+    }
+    Ok((issue, pipeline.to_string()))
+}
+
+/// Move with issue and pipeline resolved against the workspace and the board
+struct PlannedMove {
+    line_no: usize,
+    issue_ref: IssueRef,
+    repo_id: u64,
+    pipeline_name: String,
+    pipeline_id: String,
+}
+
+/// Lists the moves with issue titles and asks to proceed. Refuses to move
+/// when there is no terminal to ask, rather than waiting for the input
+/// Titles of the issues by repository id and number, so that repositories of the same name
+/// in different organizations are told apart
+fn issue_titles(list: Vec<ZenhubIssueInfo>) -> HashMap<(u64, u64), String> {
+    list.into_iter()
+        .filter_map(|x| Some(((x.repo_id?, x.issue_number), x.title)))
+        .collect()
+}
+
+async fn confirm_moves(
+    opt: &Opt,
+    repositories: Vec<ZenhubRepository>,
+    planned: &[PlannedMove],
+) -> Result<bool, Box<dyn Error>> {
+    if !atty::is(atty::Stream::Stdin) {
+        return Err("refusing to move issues without confirmation, pass --yes".into());
+    }
+    let filter = ZenhubIssuesFilter {
+        include_prs: true,
+        ..Default::default()
+    };
+    let titles = issue_titles(read_issues(opt.clone(), repositories, &filter).await?.list);
+    for plan in planned {
+        let key = (plan.repo_id, plan.issue_ref.issue_number);
+        let title = titles.get(&key).map_or("(unknown title)", |t| t.trim());
+        eprintln!("{} {:?} -> {}", plan.issue_ref, title, plan.pipeline_name);
+    }
+    eprint!("Move {} issue(s)? [y/N] ", planned.len());
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+async fn run_move(
+    opt: Opt,
+    issue: Option<String>,
+    pipeline: Option<String>,
+    from_file: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let batch = from_file.is_some() || issue.as_deref() == Some("-");
+    let lines: Vec<(usize, String)> = match (&from_file, issue, pipeline) {
+        (Some(path), _, _) => read_batch_file(path)?,
+        (None, Some(issue), Some(pipeline)) if issue == "-" => read_stdin_refs()?
+            .into_iter()
+            .map(|(n, issue)| (n, format!("{},{}", issue, pipeline)))
+            .collect(),
+        (None, Some(issue), Some(pipeline)) => vec![(1, format!("{},{}", issue, pipeline))],
+        _ => return Err("either issue and pipeline or --from-file is required".into()),
+    };
+
+    let repositories = read_repositories(opt.clone()).await?;
+    let ids = pipeline_ids(&read_pipelines(opt.clone()).await?);
+
+    let mut failed = 0;
+    let mut planned = vec![];
+    for (line_no, line) in &lines {
+        let plan = parse_move_line(line).and_then(|(issue_ref, pipeline_name)| {
+            Ok(PlannedMove {
+                line_no: *line_no,
+                repo_id: find_repo_id(&repositories, &issue_ref)?,
+                pipeline_id: resolve_pipeline_id(&ids, &pipeline_name)?,
+                issue_ref,
+                pipeline_name,
+            })
+        });
+        match plan {
+            Ok(plan) => planned.push(plan),
+            Err(e) => {
+                failed += 1;
+                if opt.output.is_json() {
+                    MutationResult::failed("move", line.clone(), &e)
+                        .at_line(*line_no)
+                        .print();
+                } else {
+                    println!("{}\tFAILED\t{}\t{}", line_no, line, e);
+                }
+            }
+        }
+    }
+    let total = lines.len();
+    apply_moves(opt, repositories, planned, total, failed, batch).await
+}
+
+/// Moves every issue of the `from` pipeline to the `to` one, in board order
+async fn run_drain(opt: Opt, from: String, to: String) -> Result<(), Box<dyn Error>> {
+    let repositories = read_repositories(opt.clone()).await?;
+    let board = read_pipelines(opt.clone()).await?;
+    let ids = pipeline_ids(&board);
+    let from_id = resolve_pipeline_id(&ids, &from)?;
+    let to_id = resolve_pipeline_id(&ids, &to)?;
+    if from_id == to_id {
+        return Err("source and target pipelines are the same".into());
+    }
+    let source = board
+        .pipelines
+        .iter()
+        .find(|p| p._id == from_id)
+        .ok_or_else(|| format!("pipeline {:?} not found", from))?;
+    let pipeline_name = board
+        .pipelines
+        .iter()
+        .find(|p| p._id == to_id)
+        .map_or(to, |p| p.name.clone());
+
+    let mut planned = vec![];
+    for (n, issue) in source.issues.iter().flatten().enumerate() {
+        let repo = match repositories.iter().find(|r| r.gh_id == issue.repo_id) {
+            Some(repo) => repo,
+            None => {
+                eprintln!(
+                    "Warning: skipping #{} of repository {} which is not in the workspace",
+                    issue.issue_number, issue.repo_id
+                );
+                continue;
+            }
+        };
+        planned.push(PlannedMove {
+            line_no: n + 1,
+            issue_ref: IssueRef {
+                owner_name: Some(repo.owner_name.clone()),
+                repo_name: repo.name.clone(),
+                issue_number: issue.issue_number,
+            },
+            repo_id: issue.repo_id,
+            pipeline_name: pipeline_name.clone(),
+            pipeline_id: to_id.clone(),
+        });
+    }
+    if planned.is_empty() {
+        eprintln!("Nothing to move, pipeline {:?} is empty", source.name);
+        return Ok(());
+    }
+    let total = planned.len();
+    apply_moves(opt, repositories, planned, total, 0, true).await
+}
+
+/// Confirms (unless `--yes`) and applies the planned moves, `failed` of `total` could not
+/// be planned. A `batch` is rate limited and ends with the summary
+async fn apply_moves(
+    mut opt: Opt,
+    repositories: Vec<ZenhubRepository>,
+    planned: Vec<PlannedMove>,
+    total: usize,
+    mut failed: usize,
+    batch: bool,
+) -> Result<(), Box<dyn Error>> {
+    if !planned.is_empty() && !opt.yes && !confirm_moves(&opt, repositories, &planned).await? {
+        return Err("aborted".into());
+    }
+    if batch {
+        start_rate_limit(&mut opt)?;
+    }
+
+    // typed inside, as the lifetime of the item comes from `run_batch`
+    let opt_ref = &opt;
+    let move_planned = |plan| async move {
+        let plan: &PlannedMove = plan;
+        let issue_ref = &plan.issue_ref;
+        let old = audit_prefetch(opt_ref, plan.repo_id, issue_ref.issue_number)
+            .await
+            .and_then(|data| {
+                data.pipeline_in(&opt_ref.workspace_id)
+                    .map(|p| p.name.clone())
+            });
+        let applied = move_issue_with_retry(
+            opt_ref.clone(),
+            plan.repo_id,
+            issue_ref.issue_number,
+            &plan.pipeline_id,
+        )
+        .await?;
+        if applied {
+            audit(opt_ref, "move", issue_ref, old.clone(), &plan.pipeline_name);
+        }
+        Ok((applied, old))
+    };
+    let report = |plan: &PlannedMove, moved: BatchResult<(bool, Option<String>)>| {
+        let issue_ref = &plan.issue_ref;
+        if opt.output.is_json() {
+            let result = match &moved {
+                Ok((_, old)) => {
+                    MutationResult::ok("move", issue_ref.to_string(), old, &plan.pipeline_name)
+                }
+                Err(e) => MutationResult::failed("move", issue_ref.to_string(), e),
+            };
+            if batch {
+                result.at_line(plan.line_no).print();
+            } else {
+                result.print();
+            }
+            return;
+        }
+        match moved.map(|(applied, _)| applied) {
+            Ok(true) => println!(
+                "{}\tOK\t{} -> {}",
+                plan.line_no, issue_ref, plan.pipeline_name
+            ),
+            Ok(false) => println!(
+                "{}\tOK\t{} already in {}",
+                plan.line_no, issue_ref, plan.pipeline_name
+            ),
+            Err(e) => println!(
+                "{}\tFAILED\t{},{}\t{}",
+                plan.line_no, issue_ref, plan.pipeline_name, e
+            ),
+        }
+    };
+    failed += run_batch(&opt, &planned, move_planned, report).await;
+    if batch {
+        finish_batch(&opt, "Moved", total, failed).await;
+    } else if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Whether JSON is pretty-printed: with `--pretty-json`, or to a terminal unless `--compact`
+/// is given; jsonl is always compact
+fn pretty_json(opt: &Opt) -> bool {
+    if opt.output == OutputFormat::Jsonl || opt.compact {
+        return false;
+    }
+    opt.pretty_json || atty::is(atty::Stream::Stdout)
+}
+
+/// Prints YAML for `--output yaml`, otherwise JSON, pretty or compact as `pretty_json` tells
+fn print_data<T: Serialize>(opt: &Opt, value: &T) -> Result<(), Box<dyn Error>> {
+    if opt.output == OutputFormat::Yaml {
+        print!("{}", serde_yaml::to_string(value)?);
+    } else if pretty_json(opt) {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", serde_json::to_string(value)?);
+    }
+    Ok(())
+}
+
+async fn run_board(opt: Opt, raw: bool) -> Result<(), Box<dyn Error>> {
+    if opt.output.is_delimited() {
+        return Err("board prints JSON or text, --output csv and tsv are not supported".into());
+    }
+    let mut board = read_pipelines(opt.clone()).await?;
+    if raw || opt.output != OutputFormat::Text {
+        for p in board.pipelines.iter_mut() {
+            p.issue_count = p.issues.as_ref().map(|v| v.len());
+        }
+        return print_data(&opt, &board);
+    }
+    for p in board.pipelines {
+        let count = p.issues.as_ref().map_or(0, |v| v.len());
+        println!("{}\t{}\t{}", p._id, count, p.name);
+    }
+    Ok(())
+}
+
+async fn run_user(opt: Opt) -> Result<(), Box<dyn Error>> {
+    let resp_user = read_user(opt.clone()).await?;
+    match opt.output {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml => {
+            print_data(&opt, &resp_user)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            if opt.output == OutputFormat::Csv && !opt.no_header {
+                println!("{}", delimited_row(&opt, &["username", "email"]));
+            }
+            println!(
+                "{}",
+                delimited_row(&opt, &[&resp_user.github.username, &resp_user.github.email])
+            );
+        }
+        OutputFormat::Text => println!(
+            "User\t{}\t{}",
+            resp_user.github.username, resp_user.github.email
+        ),
+    }
+    Ok(())
+}
+
+async fn run_workspaces(opt: Opt) -> Result<(), Box<dyn Error>> {
+    let workspaces = read_workspaces(opt.clone()).await?;
+    write_workspaces_cache(&workspaces);
+    match opt.output {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml => {
+            print_data(&opt, &workspaces)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            if opt.output == OutputFormat::Csv && !opt.no_header {
+                println!("{}", delimited_row(&opt, &["id", "name"]));
+            }
+            for w in workspaces {
+                println!("{}", delimited_row(&opt, &[&w.id, &w.name]));
+            }
+        }
+        OutputFormat::Text => {
+            for w in workspaces {
+                println!("{}\t{}", w.id, w.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_auth_check(opt: Opt) -> Result<(), Box<dyn Error>> {
+    // the token is checked on ZenHub, a cached user proves nothing
+    match fetch_user(opt.clone()).await {
+        Ok(resp_user) => {
+            write_user_cache(&opt, &resp_user);
+            println!("OK as {}", resp_user.github.username);
+            Ok(())
+        }
+        Err(e) => {
+            let status = e.downcast_ref::<reqwest::Error>().map(|e| e.status());
+            match status {
+                Some(Some(reqwest::StatusCode::UNAUTHORIZED)) => {
+                    eprintln!("Invalid token");
+                    std::process::exit(3);
+                }
+                Some(None) => {
+                    eprintln!("Network failure: {}", e);
+                    std::process::exit(4);
+                }
+                _ => Err(e),
+            }
+        }
+    }
+}
+
+async fn run_repos(opt: Opt, raw: bool) -> Result<(), Box<dyn Error>> {
+    let workspace = read_workspace(opt.clone()).await?;
+    if raw {
+        return print_data(&opt, &workspace);
+    }
+    let repositories = workspace.repositories;
+    match opt.output {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Yaml => {
+            print_data(&opt, &repositories)?
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            if opt.output == OutputFormat::Csv && !opt.no_header {
+                println!("{}", delimited_row(&opt, &["gh_id", "owner", "name"]));
+            }
+            for repo in repositories {
+                let gh_id = repo.gh_id.to_string();
+                println!(
+                    "{}",
+                    delimited_row(&opt, &[&gh_id, &repo.owner_name, &repo.name])
+                );
+            }
+        }
+        OutputFormat::Text => {
+            for repo in repositories {
+                println!("{}\t{}/{}", repo.gh_id, repo.owner_name, repo.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `--no-color` wins over `--color always`, which wins over NO_COLOR env variable.
+/// With `--color auto` styling is enabled only for a terminal, unless
+/// a non-empty `NO_COLOR` env variable is set (https://no-color.org)
+fn use_color(opt: &Opt) -> bool {
+    if opt.no_color {
+        return false;
+    }
+    match opt.color {
+        ColorMode::Never => return false,
+        ColorMode::Always => return true,
+        ColorMode::Auto => {}
+    }
+    if let Some(v) = std::env::var_os("NO_COLOR") {
+        if !v.is_empty() {
+            return false;
+        }
+    }
+    atty::is(atty::Stream::Stdout)
+}
+
+fn paint(color: bool, code: &str, text: &str) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn paint_state(color: bool, state: &str) -> String {
+    match state {
+        "open" => paint(color, "32", state),
+        "closed" => paint(color, "31", state),
+        _ => state.to_string(),
+    }
+}
+
+/// Repository name, prefixed with organization for `--show-org`
+fn repo_display(opt: &Opt, i: &ZenhubIssueInfo) -> String {
+    match &i.organization_name {
+        Some(org) if opt.show_org => format!("{}/{}", org, i.repo_name),
+        _ => i.repo_name.clone(),
+    }
+}
+
+/// Duration in its two largest units, like `2d 4h`, `3w` or `5m` (`2 days 4 hours` with
+/// `--duration-style long`), the sign is ignored
+fn humanize_duration(opt: &Opt, d: chrono::Duration) -> String {
+    const UNITS: [(i64, &str, &str); 4] = [
+        (7 * 24 * 60, "w", "week"),
+        (24 * 60, "d", "day"),
+        (60, "h", "hour"),
+        (1, "m", "minute"),
+    ];
+    let mut minutes = d.num_minutes().abs();
+    let mut parts = vec![];
+    for (size, short, long) in UNITS.iter() {
+        let n = minutes / size;
+        minutes %= size;
+        if n == 0 || parts.len() == 2 {
+            if !parts.is_empty() {
+                // units are adjacent: `2w 3h` would hide the days
+                break;
+            }
+            continue;
+        }
+        parts.push(match opt.duration_style {
+            DurationStyle::Short => format!("{}{}", n, short),
+            DurationStyle::Long if n == 1 => format!("1 {}", long),
+            DurationStyle::Long => format!("{} {}s", n, long),
+        });
+    }
+    match (parts.is_empty(), opt.duration_style) {
+        (true, DurationStyle::Short) => "0m".to_string(),
+        (true, DurationStyle::Long) => "0 minutes".to_string(),
+        (false, _) => parts.join(" "),
+    }
+}
+
+/// `4d ago` or `in 3d` of the ZenHub or GitHub datetime, none when it cannot be parsed
+fn humanize_since(opt: &Opt, datetime: &str) -> Option<String> {
+    let d = Utc::now() - parse_zenhub_datetime(datetime)?;
+    let text = humanize_duration(opt, d);
+    Some(if d < chrono::Duration::zero() {
+        format!("in {}", text)
+    } else {
+        format!("{} ago", text)
+    })
+}
+
+/// Datetime followed by how long ago it was, for `--fields-all`
+fn datetime_with_age(opt: &Opt, datetime: Option<&str>) -> String {
+    let datetime = datetime.unwrap_or_default();
+    match humanize_since(opt, datetime) {
+        Some(since) => format!("{} ({})", datetime, since),
+        None => datetime.to_string(),
+    }
+}
+
+/// Estimate with `--estimate-precision` decimal places
+fn format_estimate(opt: &Opt, value: f32) -> String {
+    match opt.estimate_precision {
+        Some(precision) => format!("{:.*}", precision, value),
+        None => format!("{}", value),
+    }
+}
+
+/// Summary fields of the pipeline header, shared by all the output modes
+fn pipeline_summary(opt: &Opt, pipeline: &ZenhubPipelineInfo) -> String {
+    if opt.only_unestimated {
+        return format!("needs estimate: {}", pipeline.list.len());
+    }
+    let mut summary = if opt.sum_field == SumField::Count {
+        format!("count: {}", pipeline.list.len())
+    } else {
+        format!(
+            "count: {}, {}: {}, not estimated: {}",
+            pipeline.list.len(),
+            opt.sum_field.name(),
+            format_estimate(opt, pipeline.estimate),
+            pipeline.not_estimated
+        )
+    };
+    if opt.stats {
+        match estimate_stats(&pipeline.list) {
+            Some((mean, median, max)) => summary.push_str(&format!(
+                ", mean: {}, median: {}, max: {}",
+                format_estimate(opt, mean),
+                format_estimate(opt, median),
+                format_estimate(opt, max)
+            )),
+            None => summary.push_str(", mean: -, median: -, max: -"),
+        }
+    }
+    summary
+}
+
+/// Share of the pipeline estimate above which its top assignee is flagged
+const CONCENTRATED_SHARE: f32 = 50.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ZenhubAssigneeCount {
+    pipeline: String,
+    /// distinct assignees of the issues
+    assignees: usize,
+    /// assignee with the largest estimate
+    top: Option<String>,
+    /// percent of the pipeline estimate held by `top`, an issue counts for each assignee
+    top_share: Option<f32>,
+    /// `top_share` is above `CONCENTRATED_SHARE`
+    concentrated: bool,
+}
+
+impl ZenhubAssigneeCount {
+    fn new(opt: &Opt, pipeline: &ZenhubPipelineInfo) -> Self {
+        let mut per_login: HashMap<&str, f32> = HashMap::new();
+        let mut total = 0.0;
+        for x in &pipeline.list {
+            let value = opt.sum_field.value(x).unwrap_or(0.0);
+            total += value;
+            let mut logins: Vec<&str> = x.assignees.iter().map(|a| a.login.as_str()).collect();
+            if logins.is_empty() {
+                logins.extend(x.assignee.as_ref().map(|a| a.login.as_str()));
+            }
+            for login in logins {
+                *per_login.entry(login).or_default() += value;
+            }
+        }
+        // ties go to the first login by name, so that the output is stable
+        let top = per_login
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(login, value)| (login.to_string(), *value));
+        let top_share = match &top {
+            Some((_, value)) if total > 0.0 => Some(value / total * 100.0),
+            _ => None,
+        };
+        ZenhubAssigneeCount {
+            pipeline: pipeline.title.clone(),
+            assignees: per_login.len(),
+            top: top.map(|(login, _)| login),
+            top_share,
+            concentrated: top_share.is_some_and(|share| share > CONCENTRATED_SHARE),
+        }
+    }
+}
+
+/// Table of `--assignee-count`
+fn display_assignee_counts(opt: &Opt, list: &[ZenhubPipelineInfo]) -> Result<(), Box<dyn Error>> {
+    let counts: Vec<ZenhubAssigneeCount> = list
+        .iter()
+        .map(|p| ZenhubAssigneeCount::new(opt, p))
+        .collect();
+    if opt.output.is_delimited() {
+        if opt.output == OutputFormat::Csv && !opt.no_header {
+            let header = ["pipeline", "assignees", "top", "top_share", "concentrated"];
+            println!("{}", delimited_row(opt, &header));
+        }
+        for c in counts {
+            let assignees = c.assignees.to_string();
+            let share = c
+                .top_share
+                .map(|share| format!("{:.0}", share))
+                .unwrap_or_default();
+            let row = [
+                c.pipeline.as_str(),
+                &assignees,
+                c.top.as_deref().unwrap_or(""),
+                &share,
+                if c.concentrated { "yes" } else { "no" },
+            ];
+            println!("{}", delimited_row(opt, &row));
+        }
+        return Ok(());
+    }
+    if opt.output != OutputFormat::Text {
+        return print_data(opt, &counts);
+    }
+    println!("Pipeline\tAssignees\tTop\tShare");
+    for c in counts {
+        let share = c
+            .top_share
+            .map(|share| format!("{:.0}%", share))
+            .unwrap_or_default();
+        let line = format!(
+            "{}\t{}\t{}\t{}{}",
+            c.pipeline,
+            c.assignees,
+            c.top.as_deref().unwrap_or(""),
+            share,
+            if c.concentrated { "\t!" } else { "" }
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// Mean, median and max of estimated issues, unestimated are ignored
+fn estimate_stats(list: &[ZenhubIssueInfo]) -> Option<(f32, f32, f32)> {
+    let mut estimates: Vec<f32> = list.iter().filter_map(|i| i.estimate).collect();
+    if estimates.is_empty() {
+        return None;
+    }
+    estimates.sort_by(f32::total_cmp);
+    let n = estimates.len();
+    let mean = estimates.iter().sum::<f32>() / n as f32;
+    let median = if n.is_multiple_of(2) {
+        (estimates[n / 2 - 1] + estimates[n / 2]) / 2.0
+    } else {
+        estimates[n / 2]
+    };
+    Some((mean, median, estimates[n - 1]))
+}
+
+const ESTIMATE_BUCKETS: [f32; 7] = [0.0, 1.0, 2.0, 3.0, 5.0, 8.0, 13.0];
+
+/// Counts of issues per estimate bucket, each estimate goes to the smallest bucket
+/// that holds it, anything above 13 goes to the last one. Unestimated are counted last
+fn estimate_histogram(list: &[ZenhubIssueInfo]) -> Vec<(String, usize)> {
+    let mut counts = vec![0; ESTIMATE_BUCKETS.len() + 1];
+    for i in list {
+        let bucket = match i.estimate {
+            Some(est) => ESTIMATE_BUCKETS
+                .iter()
+                .position(|b| est <= *b)
+                .unwrap_or(ESTIMATE_BUCKETS.len() - 1),
+            None => ESTIMATE_BUCKETS.len(),
+        };
+        counts[bucket] += 1;
+    }
+    let mut labels: Vec<String> = ESTIMATE_BUCKETS.iter().map(|b| format!("{}", b)).collect();
+    labels[ESTIMATE_BUCKETS.len() - 1].push('+');
+    labels.push("none".to_string());
+    labels.into_iter().zip(counts).collect()
+}
+
+fn histogram_lines(list: &[ZenhubIssueInfo]) -> Vec<String> {
+    estimate_histogram(list)
+        .into_iter()
+        .map(|(label, count)| {
+            let line = format!("{:>5} | {:>3} {}", label, count, "#".repeat(count));
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Every field of the issue as `name: value` lines, for `--fields-all`
+fn issue_field_lines(opt: &Opt, i: &ZenhubIssueInfo) -> Vec<String> {
+    let logins = |list: &[ZenhubAssignee]| {
+        list.iter()
+            .map(|a| a.login.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let labels: Vec<String> = i
+        .labels
+        .iter()
+        .map(|l| match &l.color {
+            Some(color) => format!("{} (#{})", l.name, color),
+            None => l.name.clone(),
+        })
+        .collect();
+    let milestone = i.milestone.as_ref().map(|m| {
+        let due = m.due_on.as_deref().map(|d| match humanize_since(opt, d) {
+            Some(since) if since.starts_with("in ") => format!(", due {} ({})", d, since),
+            Some(since) => format!(", due {} (overdue, {})", d, since),
+            None => format!(", due {}", d),
+        });
+        format!(
+            "{} (#{}, {}{})",
+            m.title,
+            m.number,
+            m.state,
+            due.unwrap_or_default()
+        )
+    });
+    let parent_epics: Vec<String> = i
+        .parent_epics
+        .iter()
+        .map(|e| format!("{}:{}", e.repo_id, e.issue_number))
+        .collect();
+    let fields = [
+        ("title", i.title.trim().to_string()),
+        ("state", i.state.clone()),
+        ("url", i.html_url.clone()),
+        (
+            "pipeline",
+            i.pipeline
+                .as_ref()
+                .map(|p| p.name.clone())
+                .unwrap_or_default(),
+        ),
+        (
+            "estimate",
+            match i.estimate {
+                Some(e) if i.estimate_derived => {
+                    format!("{} (from label)", format_estimate(opt, e))
+                }
+                Some(e) => format_estimate(opt, e),
+                None => String::new(),
+            },
+        ),
+        (
+            "assignee",
+            i.assignee
+                .as_ref()
+                .map(|a| a.login.clone())
+                .unwrap_or_default(),
+        ),
+        ("assignees", logins(&i.assignees)),
+        (
+            "author",
+            i.user.as_ref().map(|a| a.login.clone()).unwrap_or_default(),
+        ),
+        ("labels", labels.join(", ")),
+        ("milestone", milestone.unwrap_or_default()),
+        ("epic", if i.is_epic { "yes" } else { "no" }.to_string()),
+        ("parent epics", parent_epics.join(", ")),
+        ("epic titles", i.epic_titles.join(", ")),
+        ("created", datetime_with_age(opt, Some(&i.created_at))),
+        ("updated", datetime_with_age(opt, i.updated_at.as_deref())),
+        ("closed", datetime_with_age(opt, i.closed_at.as_deref())),
+    ];
+    let mut lines = vec![format!("{}:{}", repo_display(opt, i), i.issue_number)];
+    for (name, value) in fields.iter() {
+        let line = format!("  {:<14}{}", format!("{}:", name), value);
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Tab-separated `--fields` of the issue
+fn issue_fields_row(opt: &Opt, color: bool, i: &ZenhubIssueInfo) -> String {
+    let joined = |names: Vec<&str>| names.join(",");
+    let row: Vec<String> = opt
+        .fields
+        .iter()
+        .map(|field| match field {
+            IssueField::Number => format!("{}:{}", repo_display(opt, i), i.issue_number),
+            IssueField::Title => i.title.trim().to_string(),
+            IssueField::Estimate => i
+                .estimate
+                .map(|e| {
+                    let derived_str = if i.estimate_derived { "*" } else { "" };
+                    format!("{}h{}", format_estimate(opt, e), derived_str)
+                })
+                .unwrap_or_default(),
+            IssueField::State => paint_state(color, &i.state),
+            IssueField::Assignee if i.assignees.is_empty() => i
+                .assignee
+                .as_ref()
+                .map(|a| a.login.clone())
+                .unwrap_or_default(),
+            IssueField::Assignee => joined(i.assignees.iter().map(|a| a.login.as_str()).collect()),
+            IssueField::Labels => joined(i.labels.iter().map(|l| l.name.as_str()).collect()),
+            IssueField::Milestone => i
+                .milestone
+                .as_ref()
+                .map(|m| m.title.clone())
+                .unwrap_or_default(),
+            IssueField::Pipeline => i
+                .pipeline
+                .as_ref()
+                .map(|p| p.name.clone())
+                .unwrap_or_default(),
+            IssueField::Author => i.user.as_ref().map(|u| u.login.clone()).unwrap_or_default(),
+            IssueField::Created => i.created_at.clone(),
+            IssueField::Updated => i.updated_at.clone().unwrap_or_default(),
+            IssueField::Url => i.html_url.clone(),
+            IssueField::Age => parse_zenhub_datetime(&i.created_at)
+                .map(|created| humanize_duration(opt, Utc::now() - created))
+                .unwrap_or_default(),
+            IssueField::Since => i
+                .updated_at
+                .as_deref()
+                .and_then(|updated| humanize_since(opt, updated))
+                .unwrap_or_default(),
+        })
+        .collect();
+    row.join("\t")
+}
+
+/// Whether the issue is assigned to one of `--highlight` logins
+fn is_highlighted(opt: &Opt, i: &ZenhubIssueInfo) -> bool {
+    assignee_logins(i).any(|login| opt.highlight.iter().any(|h| h.eq_ignore_ascii_case(login)))
+}
+
+/// Row of the issue, bold (or `* ` marked without colors) with `--highlight`
+fn issue_row(opt: &Opt, color: bool, i: &ZenhubIssueInfo) -> String {
+    let row = if opt.fields.is_empty() {
+        issue_columns(opt, color, i)
+    } else {
+        issue_fields_row(opt, color, i)
+    };
+    if opt.highlight.is_empty() || !is_highlighted(opt, i) {
+        row
+    } else if color {
+        // bold again after the resets of colored columns
+        paint(color, "1", &row.replace("\x1b[0m", "\x1b[0m\x1b[1m"))
+    } else {
+        format!("* {}", row)
+    }
+}
+
+/// `repo:number estimate state title` line of the issue, like `api:7\t2h\topen\tTitle`
+fn issue_columns(opt: &Opt, color: bool, i: &ZenhubIssueInfo) -> String {
+    let estimate_str = match i.estimate {
+        Some(est) => format_estimate(opt, est),
+        None => "".to_string(),
+    };
+    let derived_str = if i.estimate_derived { "*" } else { "" };
+    let epics_str = if i.epic_titles.is_empty() {
+        String::new()
+    } else {
+        format!("\t(epic: {})", i.epic_titles.join(", "))
+    };
+    let url_str = if opt.with_url {
+        format!("\t{}", i.html_url)
+    } else {
+        String::new()
+    };
+    format!(
+        "{}:{}\t{}h{}\t{}\t{}{}{}",
+        repo_display(opt, i),
+        i.issue_number,
+        estimate_str,
+        derived_str,
+        paint_state(color, &i.state),
+        i.title.trim(),
+        epics_str,
+        url_str,
+    )
+}
+
+/// Issues as a tree of listed epics and their children, for `--pretty`. Issues whose epics
+/// are not listed are roots; an issue of several listed epics is shown under each of them,
+/// marked as repeated after the first
+fn issue_tree_lines(opt: &Opt, color: bool, list: &[ZenhubIssueInfo]) -> Vec<String> {
+    let index: HashMap<(u64, u64), usize> = list
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.is_epic)
+        .filter_map(|(n, x)| Some(((x.repo_id?, x.issue_number), n)))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![vec![]; list.len()];
+    let mut roots = vec![];
+    for (n, x) in list.iter().enumerate() {
+        let parents: Vec<usize> = x
+            .parent_epics
+            .iter()
+            .filter_map(|e| index.get(&(e.repo_id, e.issue_number)).copied())
+            .filter(|p| *p != n)
+            .collect();
+        if parents.is_empty() {
+            roots.push(n);
+        }
+        for p in parents {
+            children[p].push(n);
+        }
+    }
+    let mut tree = IssueTree {
+        opt,
+        color,
+        list,
+        children,
+        shown: vec![false; list.len()],
+        lines: vec![],
+    };
+    for n in roots {
+        tree.node(n, "", None);
+    }
+    // epics that are children of one another have no root to be reached from
+    for n in 0..list.len() {
+        if !tree.shown[n] {
+            tree.node(n, "", None);
+        }
+    }
+    tree.lines
+}
+
+struct IssueTree<'a> {
+    opt: &'a Opt,
+    color: bool,
+    list: &'a [ZenhubIssueInfo],
+    children: Vec<Vec<usize>>,
+    shown: Vec<bool>,
+    lines: Vec<String>,
+}
+
+impl IssueTree<'_> {
+    /// Adds the issue after its branch (`├── `, `└── `), then its children, once
+    fn node(&mut self, n: usize, indent: &str, last: Option<bool>) {
+        let (branch, child_indent) = match last {
+            None => ("", String::new()),
+            Some(true) => ("└── ", format!("{}    ", indent)),
+            Some(false) => ("├── ", format!("{}│   ", indent)),
+        };
+        let repeated = if self.shown[n] { "\t(repeated)" } else { "" };
+        self.lines.push(format!(
+            "{}{}{}{}",
+            indent,
+            branch,
+            issue_row(self.opt, self.color, &self.list[n]),
+            repeated
+        ));
+        if self.shown[n] {
+            return;
+        }
+        self.shown[n] = true;
+        let children = self.children[n].clone();
+        for (k, child) in children.iter().enumerate() {
+            self.node(*child, &child_indent, Some(k + 1 == children.len()));
+        }
+    }
+}
+
+/// Pipeline of `--collapse-closed`: has issues, all of them closed
+fn is_collapsed(opt: &Opt, pipeline: &ZenhubPipelineInfo) -> bool {
+    opt.collapse_closed
+        && opt.all_pipelines
+        && !opt.expand_all
+        && !pipeline.list.is_empty()
+        && pipeline.list.iter().all(|i| i.state == "closed")
+}
+
+/// Text lines of the pipeline: its header, then issues as rows, trees or fields
+fn pipeline_lines(opt: &Opt, color: bool, pipeline: &ZenhubPipelineInfo) -> Vec<String> {
+    if is_collapsed(opt, pipeline) {
+        let line = format!(
+            "## -- {} ({}, all closed, collapsed)",
+            pipeline.title,
+            pipeline_summary(opt, pipeline)
+        );
+        return vec![paint(color, "1", &line)];
+    }
+    let mut lines = vec![];
+    if !opt.no_header {
+        let header = format!(
+            "## -- {} ({})",
+            pipeline.title,
+            pipeline_summary(opt, pipeline)
+        );
+        lines.push(paint(color, "1", &header));
+        if pipeline.list.is_empty() {
+            // the fetch succeeded, nothing matched
+            lines.push("(no issues)".to_string());
+            return lines;
+        }
+    }
+    if opt.pretty && !opt.fields_all {
+        lines.extend(issue_tree_lines(opt, color, &pipeline.list));
+    } else {
+        for i in &pipeline.list {
+            if opt.fields_all {
+                lines.extend(issue_field_lines(opt, i));
+                continue;
+            }
+            lines.push(issue_row(opt, color, i));
+        }
+    }
+    if opt.histogram {
+        lines.extend(histogram_lines(&pipeline.list));
+    }
+    lines
+}
+
+fn display_issues(opt: &Opt, pipeline: ZenhubPipelineInfo) {
+    for line in pipeline_lines(opt, use_color(opt), &pipeline) {
+        println!("{}", line);
+    }
+}
+
+/// `--no-dotenv` among the flags before the first argument that is not a flag (a command
+/// or an option value) or `--`, which could not be parsed without `.env`
+fn has_no_dotenv_flag(args: impl Iterator<Item = std::ffi::OsString>) -> bool {
+    for arg in args {
+        if arg == "--no-dotenv" {
+            return true;
+        }
+        if arg == "--" || !arg.to_string_lossy().starts_with('-') {
+            return false;
+        }
+    }
+    false
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    // before parsing, so that `.env` provides defaults of env-backed options
+    let no_dotenv = match Opt::from_iter_safe(std::env::args_os()) {
+        Ok(opt) => opt.no_dotenv,
+        // options missing without `.env`, or `--help`
+        Err(_) => has_no_dotenv_flag(std::env::args_os().skip(1)),
+    };
+    if !no_dotenv {
+        match dotenvy::dotenv() {
+            Err(e) if !e.not_found() => eprintln!("Warning: cannot load .env: {}", e),
+            _ => {}
+        }
+    }
+    let mut opt = Opt::from_args();
+    resolve_agent(&mut opt);
+    let agent_opt = opt.clone();
+    tokio::select! {
+        res = run(opt) => {
+            if let Err(e) = &res {
+                agent_hint(&agent_opt, e.as_ref());
+            }
+            res
+        }
+        _ = tokio::signal::ctrl_c() => {
+            eprintln!("Interrupted");
+            std::process::exit(130);
+        }
+    }
+}
+
+async fn run(mut opt: Opt) -> Result<(), Box<dyn Error>> {
+    if opt.check_update && std::env::var_os("ZENHUB_NO_UPDATE_CHECK").is_none() {
+        check_update(&opt).await;
+    }
+    clamp_page_size(&mut opt);
+    if let (true, Some(view)) = (opt.fields.is_empty(), opt.view) {
+        opt.fields = view.fields();
+    }
+    check_headers(&opt)?;
+    apply_filter_preset(&mut opt)?;
+    default_stdin_issue(&mut opt);
+    read_assignee_file(&mut opt)?;
+    resolve_workspace(&mut opt).await?;
+    resolve_aliases(&mut opt).await?;
+    if opt.show_workspace {
+        let workspace = read_workspace(opt.clone()).await?;
+        opt.workspace_header = Some(WorkspaceHeader {
+            id: workspace.id,
+            name: workspace.name,
+            description: workspace.description,
+        });
+    }
+
+    match opt.cmd.clone() {
+        Some(Command::Move {
+            issue,
+            target,
+            from_file,
+        }) => run_move(opt, issue, target, from_file).await,
+        Some(Command::Drain { from, to }) => run_drain(opt, from, to).await,
+        Some(Command::Estimate {
+            issue,
+            value,
+            from_file,
+        }) => match (from_file, issue, value) {
+            (Some(path), _, _) => run_estimate_batch(opt, read_batch_file(&path)?).await,
+            (None, Some(issue), Some(value)) if issue == "-" => {
+                let lines = read_stdin_refs()?
+                    .into_iter()
+                    .map(|(n, issue)| (n, format!("{},{}", issue, value)))
+                    .collect();
+                run_estimate_batch(opt, lines).await
+            }
+            (None, Some(issue), Some(value)) => run_estimate(opt, issue, value).await,
+            _ => Err("either issue and value or --from-file is required".into()),
+        },
+        Some(Command::Show {
+            issue: Some(issue),
+            with_body,
+        }) if issue == "-" => {
+            for (_, issue) in read_stdin_refs()? {
+                run_show(opt.clone(), issue, with_body).await?;
+            }
+            Ok(())
+        }
+        Some(Command::Show {
+            issue: Some(issue),
+            with_body,
+        }) => run_show(opt, issue, with_body).await,
+        Some(Command::Show { issue: None, .. }) => {
+            Err("issue is required, or issue references piped to stdin".into())
+        }
+        Some(Command::Epic { issue }) => run_epic(opt, issue).await,
+        Some(Command::Graph) => run_graph(opt).await,
+        Some(Command::Validate { checks }) => run_validate(opt, checks).await,
+        Some(Command::Search { query, limit }) => run_search(opt, query, limit).await,
+        Some(Command::Board { raw }) => run_board(opt, raw).await,
+        Some(Command::Issues) => run_issues(opt).await,
+        Some(Command::Tui) => tui::run_tui(opt).await,
+        Some(Command::User) => run_user(opt).await,
+        Some(Command::Repos { raw }) => run_repos(opt, raw).await,
+        Some(Command::Workspaces) => run_workspaces(opt).await,
+        Some(Command::Auth { auth }) => match auth {
+            Auth::Check => run_auth_check(opt).await,
+        },
+        Some(Command::Report { report }) => match report {
+            Report::Burndown => run_burndown(opt).await,
+            Report::Velocity { weeks } => run_velocity(opt, weeks).await,
+            Report::Standup => run_standup(opt).await,
+        },
+        Some(Command::Milestone { milestone }) => match milestone {
+            Milestone::Set { issue, title } => run_milestone_set(opt, issue, title).await,
+        },
+        Some(Command::Comment { issue, text, file }) => run_comment(opt, issue, text, file).await,
+        Some(Command::Export { export }) => match export {
+            Export::GithubProjects => run_export_github_projects(opt).await,
+        },
+        None if !opt.set.is_empty() => {
+            let (issue, set) = (opt.issue.clone(), opt.set.clone());
+            run_move(opt, Some(issue), Some(set), None).await
+        }
+        None if opt.estimate > 0.0 => {
+            let (issue, value) = (opt.issue.clone(), opt.estimate);
+            run_estimate(opt, issue, value).await
+        }
+        None => run_pipelines(opt).await,
+    }
+}
+
+/// Drops repeated pipeline names, keeping the first-seen order
+fn dedup_pipelines(pipelines: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut result = vec![];
+    for p in pipelines {
+        if seen.insert(p.clone()) {
+            result.push(p.clone());
+        } else {
+            eprintln!("Warning: pipeline {:?} is given more than once, skipped", p);
+        }
+    }
+    result
+}
+
+/// Assignee to filter issues by, none when `--assignee-file` gives the team instead,
+/// `--unassigned` asks for issues of nobody or `--all-assignees` for issues of anybody
+async fn filter_assignee(opt: &Opt) -> Result<Option<String>, Box<dyn Error>> {
+    if opt.team.is_empty() && !opt.unassigned && !opt.all_assignees {
+        Ok(Some(resolve_assignee(opt).await?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Reads logins of `--assignee-file`, skipping blanks and `#` comments
+fn read_assignee_file(opt: &mut Opt) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &opt.assignee_file {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        opt.team = content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.trim_start_matches('@').to_string())
+            .collect();
+        if opt.team.is_empty() {
+            return Err(format!("no logins in {}", path.display()).into());
+        }
+    }
+    Ok(())
+}
+
+async fn resolve_assignee(opt: &Opt) -> Result<String, Box<dyn Error>> {
+    match opt.assignee.as_deref() {
+        Some(login) if login != "me" => Ok(login.to_string()),
+        _ => {
+            let resp_user = read_user(opt.clone()).await.map_err(|e| {
+                format!(
+                    "cannot look up the authenticated user: {}, pass --assignee <login> instead",
+                    e
+                )
+            })?;
+            if opt.output == OutputFormat::Text && !opt.no_user_banner {
+                println!(
+                    "User\t{}\t{}",
+                    resp_user.github.username, resp_user.github.email
+                );
+            }
+            Ok(resp_user.github.username)
+        }
+    }
+}
+
+/// Single character separator for `--delimiter`, quotes and line breaks would break the quoting
+fn parse_delimiter(s: &str) -> Result<char, String> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !matches!(c, '"' | '\n' | '\r') => Ok(c),
+        (Some(_), None) => Err(format!("{:?} cannot be used as delimiter", s)),
+        _ => Err(format!(
+            "delimiter should be a single character, got {:?}",
+            s
+        )),
+    }
+}
+
+/// Quotes CSV field when it holds the delimiter, quote or line break
+fn csv_field(value: &str, delimiter: char) -> String {
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escapes tabs, line breaks and backslashes, so that each TSV value stays in its column
+fn tsv_field(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Row of `--output csv` or `tsv`
+fn delimited_row(opt: &Opt, row: &[&str]) -> String {
+    if opt.output == OutputFormat::Tsv {
+        let row: Vec<String> = row.iter().map(|v| tsv_field(v)).collect();
+        row.join("\t")
+    } else {
+        let row: Vec<String> = row.iter().map(|v| csv_field(v, opt.delimiter)).collect();
+        row.join(&opt.delimiter.to_string())
+    }
+}
+
+/// Issues as `--output csv` or `tsv` rows, in the column order documented for `--output`
+fn display_csv(opt: &Opt, list: &[ZenhubPipelineInfo]) {
+    if opt.output == OutputFormat::Csv && !opt.no_header {
+        let mut header = vec!["pipeline", "repo", "number", "estimate", "state", "title"];
+        if opt.with_url {
+            header.push("url");
+        }
+        println!("{}", delimited_row(opt, &header));
+    }
+    for pipeline in list {
+        for i in &pipeline.list {
+            let estimate_str = i
+                .estimate
+                .map(|e| format_estimate(opt, e))
+                .unwrap_or_default();
+            let pipeline_name = i.pipeline.as_ref().map_or(&pipeline.title, |p| &p.name);
+            let repo = repo_display(opt, i);
+            let number = i.issue_number.to_string();
+            let mut row = vec![
+                pipeline_name.as_str(),
+                &repo,
+                &number,
+                &estimate_str,
+                i.state.as_str(),
+                i.title.trim(),
+            ];
+            if opt.with_url {
+                row.push(&i.html_url);
+            }
+            println!("{}", delimited_row(opt, &row));
+        }
+    }
+}
+
+/// Item of `export github-projects`. Columns map to GitHub Projects fields as:
+/// - Title - issue title
+/// - URL - GitHub URL of the issue, which is how Projects adds the item
+/// - Status - ZenHub pipeline, as options of the single select `Status` field
+/// - Estimate - ZenHub estimate, for a number field; empty when not estimated
+/// - Assignees - logins, comma-separated
+/// - Labels - label names, comma-separated
+/// - Milestone - milestone title
+/// - Repository - `owner/name`
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct GithubProjectsItem {
+    title: String,
+    #[serde(rename = "URL")]
+    url: String,
+    status: String,
+    estimate: Option<f32>,
+    assignees: String,
+    labels: String,
+    milestone: String,
+    repository: String,
+}
+
+impl GithubProjectsItem {
+    const COLUMNS: [&'static str; 8] = [
+        "Title",
+        "URL",
+        "Status",
+        "Estimate",
+        "Assignees",
+        "Labels",
+        "Milestone",
+        "Repository",
+    ];
+
+    fn new(status: &str, i: &ZenhubIssueInfo) -> Self {
+        let mut assignees: Vec<&str> = i.assignees.iter().map(|a| a.login.as_str()).collect();
+        if assignees.is_empty() {
+            assignees.extend(i.assignee.as_ref().map(|a| a.login.as_str()));
+        }
+        GithubProjectsItem {
+            title: i.title.trim().to_string(),
+            url: i.html_url.clone(),
+            status: i.pipeline.as_ref().map_or(status, |p| &p.name).to_string(),
+            estimate: i.estimate,
+            assignees: assignees.join(","),
+            labels: i
+                .labels
+                .iter()
+                .map(|l| l.name.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            milestone: i
+                .milestone
+                .as_ref()
+                .map(|m| m.title.clone())
+                .unwrap_or_default(),
+            repository: match &i.organization_name {
+                Some(org) => format!("{}/{}", org, i.repo_name),
+                None => i.repo_name.clone(),
+            },
+        }
+    }
+}
+
+/// Issues of the board (or --pipeline), in board order, as GitHub Projects items
+async fn run_export_github_projects(mut opt: Opt) -> Result<(), Box<dyn Error>> {
+    if opt.output == OutputFormat::Text {
+        // CSV is the text of the export, without the user banner
+        opt.output = OutputFormat::Csv;
+    }
+    let items: Vec<GithubProjectsItem> = read_board_issues(&opt)
+        .await?
+        .iter()
+        .flat_map(|p| {
+            p.list
+                .iter()
+                .map(move |i| GithubProjectsItem::new(&p.title, i))
+        })
+        .collect();
+    match opt.output {
+        OutputFormat::Json | OutputFormat::Yaml => return print_data(&opt, &items),
+        OutputFormat::Jsonl => {
+            for item in &items {
+                println!("{}", serde_json::to_string(item)?);
+            }
+            return Ok(());
+        }
+        OutputFormat::Text | OutputFormat::Csv | OutputFormat::Tsv => {}
+    }
+    if !opt.no_header {
+        println!("{}", delimited_row(&opt, &GithubProjectsItem::COLUMNS));
+    }
+    for item in &items {
+        let estimate = item.estimate.map(|e| e.to_string()).unwrap_or_default();
+        let row = [
+            item.title.as_str(),
+            &item.url,
+            &item.status,
+            &estimate,
+            &item.assignees,
+            &item.labels,
+            &item.milestone,
+            &item.repository,
+        ];
+        println!("{}", delimited_row(&opt, &row));
+    }
+    Ok(())
+}
+
+/// Workspace printed before the listing with `--show-workspace`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct WorkspaceHeader {
+    id: String,
+    name: String,
+    description: String,
+}
+
+#[derive(Serialize)]
+struct WorkspaceListing<'a> {
+    workspace: &'a WorkspaceHeader,
+    pipelines: &'a [ZenhubPipelineInfo],
+}
+
+#[derive(Serialize)]
+struct JsonlMeta<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    workspace: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace_name: Option<&'a str>,
+    generated_at: String,
+}
+
+#[derive(Serialize)]
+struct JsonlIssue<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(flatten)]
+    issue: &'a ZenhubIssueInfo,
+}
+
+/// Issues as JSON lines, after the `meta` line that streaming consumers read first
+fn display_jsonl(opt: &Opt, list: &[ZenhubPipelineInfo]) -> Result<(), Box<dyn Error>> {
+    let meta = JsonlMeta {
+        kind: "meta",
+        workspace: &opt.workspace_id,
+        workspace_name: opt.workspace_header.as_ref().map(|w| w.name.as_str()),
+        generated_at: Utc::now().to_rfc3339(),
+    };
+    println!("{}", serde_json::to_string(&meta)?);
+    for issue in list.iter().flat_map(|p| &p.list) {
+        let line = JsonlIssue {
+            kind: "issue",
+            issue,
+        };
+        println!("{}", serde_json::to_string(&line)?);
+    }
+    Ok(())
+}
+
+fn render_pipelines(opt: &Opt, list: Vec<ZenhubPipelineInfo>) -> Result<(), Box<dyn Error>> {
+    if opt.assignee_count {
+        return display_assignee_counts(opt, &list);
+    }
+    match opt.output {
+        OutputFormat::Json | OutputFormat::Yaml => match &opt.workspace_header {
+            Some(workspace) => print_data(
+                opt,
+                &WorkspaceListing {
+                    workspace,
+                    pipelines: &list,
+                },
+            )?,
+            None => print_data(opt, &list)?,
+        },
+        OutputFormat::Jsonl => display_jsonl(opt, &list)?,
+        OutputFormat::Csv | OutputFormat::Tsv => display_csv(opt, &list),
+        OutputFormat::Text => {
+            if let Some(workspace) = &opt.workspace_header {
+                let header = match workspace.description.trim() {
+                    "" => format!("# -- {}", workspace.name),
+                    description => format!("# -- {}: {}", workspace.name, description),
+                };
+                println!("{}", paint(use_color(opt), "1", &header));
+            }
+            for pipeline in list {
+                display_issues(opt, pipeline);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_issues(opt: Opt) -> Result<(), Box<dyn Error>> {
+    if opt.watch.is_some() {
+        return run_watch(opt, false).await;
+    }
+    let list = read_all_issues(&opt).await?;
+    render_pipelines(&opt, list)
+}
+
+/// Issues of all pipelines as a single list
+async fn read_all_issues(opt: &Opt) -> Result<Vec<ZenhubPipelineInfo>, Box<dyn Error>> {
+    let username = filter_assignee(opt).await?;
+    let repositories = read_issue_repositories(opt).await?;
+    let filter = ZenhubIssuesFilter::from_opt(opt, username, None);
+    let mut issues = read_issues(opt.clone(), repositories, &filter).await?;
+    if opt.resolve_epics {
+        resolve_epics(opt, &mut issues.list).await?;
+    }
+    Ok(vec![issues])
+}
+
+/// Pipeline, estimate and state of the issue at the previous poll of `--watch`
+#[derive(Clone, PartialEq)]
+struct WatchedIssue {
+    /// `repo:number` as listed, not compared between the polls
+    issue: String,
+    pipeline: String,
+    estimate: Option<f32>,
+    state: String,
+}
+
+/// Issue of `--watch` snapshot: GitHub id of the repository and issue number, so that
+/// renames and `--show-org` do not change it. Issues of several unnamed `--repo-id` have
+/// no repository id, their repository name is there instead
+type WatchKey = (Option<u64>, String, u64);
+
+fn watch_snapshot(opt: &Opt, list: &[ZenhubPipelineInfo]) -> HashMap<WatchKey, WatchedIssue> {
+    let mut snapshot = HashMap::new();
+    for pipeline in list {
+        for i in &pipeline.list {
+            let repo = match i.repo_id {
+                Some(_) => String::new(),
+                None => format!(
+                    "{}/{}",
+                    i.organization_name.as_deref().unwrap_or(""),
+                    i.repo_name
+                ),
+            };
+            snapshot.insert(
+                (i.repo_id, repo, i.issue_number),
+                WatchedIssue {
+                    issue: format!("{}:{}", repo_display(opt, i), i.issue_number),
+                    pipeline: i
+                        .pipeline
+                        .as_ref()
+                        .map_or(pipeline.title.clone(), |p| p.name.clone()),
+                    estimate: i.estimate,
+                    state: i.state.clone(),
+                },
+            );
+        }
+    }
+    snapshot
+}
+
+/// Changes between two polls, as `time, change, issue, old -> new` lines
+fn watch_changes(
+    opt: &Opt,
+    prev: &HashMap<WatchKey, WatchedIssue>,
+    next: &HashMap<WatchKey, WatchedIssue>,
+) -> Vec<String> {
+    let color = use_color(opt);
+    let estimate_str = |e: Option<f32>| e.map_or("-".to_string(), |e| format_estimate(opt, e));
+    let time = Local::now().format("%H:%M:%S");
+    let mut lines = vec![];
+    let mut keys: Vec<(&str, &WatchKey)> = next
+        .iter()
+        .chain(prev.iter().filter(|(key, _)| !next.contains_key(*key)))
+        .map(|(key, x)| (x.issue.as_str(), key))
+        .collect();
+    keys.sort();
+    for (issue, key) in keys {
+        let mut change = |code: &str, what: &str, detail: String| {
+            let what = paint(color, code, what);
+            lines.push(format!("{}\t{}\t{}\t{}", time, what, issue, detail));
+        };
+        match (prev.get(key), next.get(key)) {
+            (None, Some(n)) => change("32", "new", n.pipeline.clone()),
+            (Some(p), None) => change("31", "gone", p.pipeline.clone()),
+            (Some(p), Some(n)) => {
+                if p.pipeline != n.pipeline {
+                    change("33", "moved", format!("{} -> {}", p.pipeline, n.pipeline));
+                }
+                if p.estimate != n.estimate {
+                    let detail = format!(
+                        "{} -> {}",
+                        estimate_str(p.estimate),
+                        estimate_str(n.estimate)
+                    );
+                    change("36", "estimated", detail);
+                }
+                if p.state != n.state {
+                    change("35", "state", format!("{} -> {}", p.state, n.state));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    lines
+}
+
+/// Polls the listing every `--watch` seconds: re-renders it, or prints the changes for `--delta`.
+/// A failed poll is reported and retried at the next interval
+async fn run_watch(mut opt: Opt, by_pipeline: bool) -> Result<(), Box<dyn Error>> {
+    let seconds = opt.watch.unwrap_or(0).max(1);
+    if opt.team.is_empty() && !opt.unassigned && !opt.all_assignees {
+        opt.assignee = Some(resolve_assignee(&opt).await?);
+    }
+    let mut prev: Option<HashMap<WatchKey, WatchedIssue>> = None;
+    loop {
+        let listed = if by_pipeline {
+            read_board_issues(&opt).await
+        } else {
+            read_all_issues(&opt).await
+        };
+        match listed {
+            Ok(list) => match &prev {
+                Some(prev_snapshot) if opt.delta => {
+                    let snapshot = watch_snapshot(&opt, &list);
+                    for line in watch_changes(&opt, prev_snapshot, &snapshot) {
+                        println!("{}", line);
+                    }
+                    prev = Some(snapshot);
+                }
+                _ => {
+                    if !opt.delta && atty::is(atty::Stream::Stdout) {
+                        print!("\x1b[2J\x1b[H");
+                    }
+                    prev = Some(watch_snapshot(&opt, &list));
+                    render_pipelines(&opt, list)?;
+                }
+            },
+            Err(e) => eprintln!("Warning: {}", e),
+        }
+        std::io::stdout().flush()?;
+        tokio::time::delay_for(std::time::Duration::from_secs(seconds)).await;
+    }
+}
+
+/// Fills in `epic_titles` of the issues. All the parent epics are looked up at once,
+/// listing epics of their repositories
+async fn resolve_epics(opt: &Opt, list: &mut [ZenhubIssueInfo]) -> Result<(), Box<dyn Error>> {
+    let refs: HashSet<(u64, u64)> = list
+        .iter()
+        .flat_map(|x| x.parent_epics.iter().map(|e| (e.repo_id, e.issue_number)))
+        .collect();
+    if refs.is_empty() {
+        return Ok(());
+    }
+    let repositories: Vec<ZenhubRepository> = read_repositories(opt.clone())
+        .await?
+        .into_iter()
+        .filter(|r| refs.iter().any(|(repo_id, _)| *repo_id == r.gh_id))
+        .collect();
+    let repo_ids: HashMap<String, u64> = repositories
+        .iter()
+        .map(|r| (r.name.clone(), r.gh_id))
+        .collect();
+    let filter = ZenhubIssuesFilter {
+        include_prs: true,
+        only_epics: true,
+        ..Default::default()
+    };
+    let mut titles: HashMap<(u64, u64), String> = HashMap::new();
+    for epic in read_issues(opt.clone(), repositories, &filter).await?.list {
+        if let Some(repo_id) = repo_ids.get(&epic.repo_name) {
+            titles.insert((*repo_id, epic.issue_number), epic.title.trim().to_string());
+        }
+    }
+    for x in list {
+        x.epic_titles = x
+            .parent_epics
+            .iter()
+            .map(|e| match titles.get(&(e.repo_id, e.issue_number)) {
+                Some(title) => title.clone(),
+                None => format!("#{}", e.issue_number),
+            })
+            .collect();
+    }
+    Ok(())
+}
+
+/// Pipeline named or described as closed or archived
+fn is_closed_pipeline(p: &ZenhubPipeline) -> bool {
+    let marks = |s: &str| {
+        let s = s.to_lowercase();
+        s.contains("closed") || s.contains("archive")
+    };
+    marks(&p.name) || p.description.as_deref().is_some_and(marks)
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct ZenhubIssuesFilter {
-    by_assignee: Option<String>,
-    by_pipeline_name: Option<String>,
+/// Issues of the given pipelines (or all pipelines of the board), fetched at once
+async fn read_board_issues(opt: &Opt) -> Result<Vec<ZenhubPipelineInfo>, Box<dyn Error>> {
+    let username = filter_assignee(opt).await?;
+    let repositories = read_issue_repositories(opt).await?;
+    let names = if opt.all_pipelines || opt.pipeline.is_empty() {
+        let board = read_pipelines(opt.clone()).await?;
+        let skip_closed = opt.all_pipelines && !opt.include_closed_pipelines;
+        board
+            .pipelines
+            .into_iter()
+            .filter(|p| !(skip_closed && is_closed_pipeline(p)))
+            .map(|p| p.name)
+            .collect()
+    } else {
+        dedup_pipelines(&opt.pipeline)
+    };
+    let names: Vec<String> = names
+        .into_iter()
+        .filter(|name| {
+            !opt.exclude_pipeline
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    let mut filter = ZenhubIssuesFilter::from_opt(opt, username, None);
+    filter.by_pipeline_names = Some(names.clone());
+    let mut all = read_issues(opt.clone(), repositories, &filter).await?;
+    if opt.resolve_epics {
+        resolve_epics(opt, &mut all.list).await?;
+    }
+    let mut list = group_by_pipeline(opt, all.list, &names);
+    match opt.sort_pipelines {
+        PipelineSort::Board => {}
+        PipelineSort::Name => list.sort_by_key(|p| p.title.to_lowercase()),
+        PipelineSort::Estimate => list.sort_by(|a, b| b.estimate.total_cmp(&a.estimate)),
+        PipelineSort::Count => list.sort_by_key(|p| std::cmp::Reverse(p.list.len())),
+    }
+    Ok(list)
 }
 
 #[derive(Serialize, Deserialize, Clone)]
-struct ZenhubPipelineInfo {
-    title: String,
-    list: Vec<ZenhubIssueInfo>,
+struct ZenhubBurndown {
+    pipeline: String,
     estimate: f32,
-    not_estimated: i32,
+    closed_estimate: f32,
+    open_estimate: f32,
+    percent_complete: Option<f32>,
 }
 
-#[allow(dead_code)]
-async fn read_issues(
-    opt: Opt,
-    repositories: Vec<ZenhubRepository>,
-    filter: &ZenhubIssuesFilter,
-) -> Result<ZenhubPipelineInfo, Box<dyn Error>> {
-    let ids = repositories
+impl ZenhubBurndown {
+    fn new(sum_field: &SumField, pipeline: String, list: &[ZenhubIssueInfo]) -> Self {
+        let mut closed_estimate = 0.0;
+        let mut open_estimate = 0.0;
+        for x in list {
+            let estimate_val = sum_field.value(x).unwrap_or(0.0);
+            if x.state == "closed" {
+                closed_estimate += estimate_val;
+            } else {
+                open_estimate += estimate_val;
+            }
+        }
+        let estimate = closed_estimate + open_estimate;
+        ZenhubBurndown {
+            pipeline,
+            estimate,
+            closed_estimate,
+            open_estimate,
+            percent_complete: if estimate > 0.0 {
+                Some(closed_estimate * 100.0 / estimate)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+async fn run_burndown(opt: Opt) -> Result<(), Box<dyn Error>> {
+    let pipelines = read_board_issues(&opt).await?;
+    let mut rows: Vec<ZenhubBurndown> = pipelines
         .iter()
-        .map(|x| format!("{}", x.gh_id))
-        .collect::<HashSet<_>>();
-    let ids_str: String = ids.iter().map(|x| &**x).collect::<Vec<_>>().join(",");
+        .map(|p| ZenhubBurndown::new(&opt.sum_field, p.title.clone(), &p.list))
+        .collect();
+    let all: Vec<ZenhubIssueInfo> = pipelines.into_iter().flat_map(|p| p.list).collect();
+    rows.push(ZenhubBurndown::new(
+        &opt.sum_field,
+        "Total".to_string(),
+        &all,
+    ));
+    if opt.output.is_delimited() {
+        if opt.output == OutputFormat::Csv && !opt.no_header {
+            let header = [
+                "pipeline",
+                "estimate",
+                "closed_estimate",
+                "open_estimate",
+                "percent_complete",
+            ];
+            println!("{}", delimited_row(&opt, &header));
+        }
+        for r in rows {
+            let done = r
+                .percent_complete
+                .map(|pct| format!("{:.0}", pct))
+                .unwrap_or_default();
+            let row = [
+                r.pipeline.as_str(),
+                &format_estimate(&opt, r.estimate),
+                &format_estimate(&opt, r.closed_estimate),
+                &format_estimate(&opt, r.open_estimate),
+                &done,
+            ];
+            println!("{}", delimited_row(&opt, &row));
+        }
+        return Ok(());
+    }
+    if opt.output != OutputFormat::Text {
+        return print_data(&opt, &rows);
+    }
+    println!("Pipeline\tTotal\tClosed\tOpen\tDone");
+    for r in rows {
+        let done = match r.percent_complete {
+            Some(pct) => format!("{:.0}%", pct),
+            None => "-".to_string(),
+        };
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            r.pipeline,
+            format_estimate(&opt, r.estimate),
+            format_estimate(&opt, r.closed_estimate),
+            format_estimate(&opt, r.open_estimate),
+            done
+        );
+    }
+    Ok(())
+}
 
-    let mut url: String = format!(
-        "{}/v5/workspaces/{}/issues?repo_ids={}",
-        opt.api_root, opt.workspace_id, ids_str
+#[derive(Serialize, Deserialize, Clone)]
+struct ZenhubVelocity {
+    /// Monday the week starts on
+    week: String,
+    closed: usize,
+    estimate: f32,
+}
+
+/// Closed issues summed per week (Monday to Sunday, UTC) over the last `weeks` weeks.
+/// Closed issues without `closed_at` cannot be placed in a week and are only counted
+fn velocity_weeks(
+    opt: &Opt,
+    list: &[ZenhubIssueInfo],
+    weeks: u32,
+    today: NaiveDate,
+) -> (Vec<ZenhubVelocity>, usize) {
+    let this_week = today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
+    let mut rows: Vec<ZenhubVelocity> = (0..weeks.max(1))
+        .rev()
+        .map(|n| ZenhubVelocity {
+            week: (this_week - chrono::Duration::weeks(n.into())).to_string(),
+            closed: 0,
+            estimate: 0.0,
+        })
+        .collect();
+    let first = this_week - chrono::Duration::weeks((weeks.max(1) - 1).into());
+    let mut undated = 0;
+    for x in list.iter().filter(|x| x.state == "closed") {
+        let closed = match x.closed_at.as_deref().and_then(parse_zenhub_datetime) {
+            Some(closed) => closed.date_naive(),
+            None => {
+                undated += 1;
+                continue;
+            }
+        };
+        if closed < first || closed > today {
+            continue;
+        }
+        let row = &mut rows[((closed - first).num_days() / 7) as usize];
+        row.closed += 1;
+        row.estimate += opt.sum_field.value(x).unwrap_or(0.0);
+    }
+    (rows, undated)
+}
+
+/// `--output csv` or `tsv` rows of the weeks: week, closed, estimate
+fn velocity_delimited_lines(opt: &Opt, rows: &[ZenhubVelocity]) -> Vec<String> {
+    let mut lines = vec![];
+    if opt.output == OutputFormat::Csv && !opt.no_header {
+        lines.push(delimited_row(opt, &["week", "closed", "estimate"]));
+    }
+    for r in rows {
+        let closed = r.closed.to_string();
+        let estimate = format_estimate(opt, r.estimate);
+        lines.push(delimited_row(opt, &[&r.week, &closed, &estimate]));
+    }
+    lines
+}
+
+async fn run_velocity(opt: Opt, weeks: u32) -> Result<(), Box<dyn Error>> {
+    let all: Vec<ZenhubIssueInfo> = read_board_issues(&opt)
+        .await?
+        .into_iter()
+        .flat_map(|p| p.list)
+        .collect();
+    let (rows, undated) = velocity_weeks(&opt, &all, weeks, Utc::now().date_naive());
+    if undated > 0 {
+        eprintln!(
+            "Warning: {} closed issues have no closed_at and are left out",
+            undated
+        );
+    }
+    if opt.output.is_delimited() {
+        for line in velocity_delimited_lines(&opt, &rows) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    if opt.output != OutputFormat::Text {
+        return print_data(&opt, &rows);
+    }
+    let max = rows.iter().map(|r| r.estimate).fold(0.0, f32::max);
+    println!("Week\tClosed\t{}", opt.sum_field.name());
+    for r in &rows {
+        // bars are scaled to at most 40 characters
+        let bar = if max > 0.0 {
+            "#".repeat((r.estimate / max * 40.0).round() as usize)
+        } else {
+            String::new()
+        };
+        let line = format!(
+            "{}\t{}\t{}\t{}",
+            r.week,
+            r.closed,
+            format_estimate(&opt, r.estimate),
+            bar
+        );
+        println!("{}", line.trim_end());
+    }
+    let total: f32 = rows.iter().map(|r| r.estimate).sum();
+    println!(
+        "Average\t\t{}",
+        format_estimate(&opt, total / rows.len() as f32)
     );
+    Ok(())
+}
 
-    url.push_str("&epics=1");
-    url.push_str("&estimates=1");
-    url.push_str("&connections=1");
-    url.push_str("&forceUpdate=0");
-    url.push_str("&pipelines=1");
-    url.push_str("&priorities=1");
-    url.push_str("&releases=1");
+/// Issue is blocked when it has a label like `blocked` or `Status: Blocked`
+fn is_blocked(x: &ZenhubIssueInfo) -> bool {
+    x.labels
+        .iter()
+        .any(|l| l.name.to_lowercase().contains("blocked"))
+}
 
-    let res = reqwest::Client::new()
-        .get(&url)
-        .headers(zenhub_headers(opt))
-        .send()
+fn standup_line(opt: &Opt, pipeline: &str, i: &ZenhubIssueInfo) -> String {
+    let estimate_str = i
+        .estimate
+        .map(|e| format!(", {}h", format_estimate(opt, e)))
+        .unwrap_or_default();
+    format!(
+        "- {}:{} {} ({}{})",
+        repo_display(opt, i),
+        i.issue_number,
+        i.title.trim(),
+        pipeline,
+        estimate_str
+    )
+}
+
+async fn run_standup(mut opt: Opt) -> Result<(), Box<dyn Error>> {
+    let login = match opt.assignee.as_deref() {
+        Some(login) if login != "me" => login.to_string(),
+        _ => read_user(opt.clone()).await?.github.username,
+    };
+    opt.assignee = Some(login.clone());
+    let pipelines = read_board_issues(&opt).await?;
+    let closed: Vec<String> = read_pipelines(opt.clone())
         .await?
-        .json::<Vec<ZenhubIssueInfo>>()
-        .await?;
-    let mut estimate: f32 = 0.0;
-    let mut not_estimated = 0;
-    let filtered = res
-        .clone()
-        .drain(..)
-        .filter(|x| {
-            let mut m = true;
-            if let Some(by_assignee) = filter.by_assignee.to_owned().take() {
-                if let Some(assignee) = x.clone().assignee.take() {
-                    m = m && (assignee.login == by_assignee);
-                } else {
-                    m = false
-                }
+        .pipelines
+        .into_iter()
+        .filter(is_closed_pipeline)
+        .map(|p| p.name)
+        .collect();
+    for line in standup_lines(&opt, &login, &pipelines, &closed) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Open issues of the user outside of the `closed` pipelines, the blocked ones apart
+fn standup_lines(
+    opt: &Opt,
+    login: &str,
+    pipelines: &[ZenhubPipelineInfo],
+    closed: &[String],
+) -> Vec<String> {
+    let mut in_progress = vec![];
+    let mut blocked = vec![];
+    let mut estimate = 0.0;
+    for p in pipelines.iter().filter(|p| !closed.contains(&p.title)) {
+        for i in p.list.iter().filter(|i| i.state != "closed") {
+            if is_blocked(i) {
+                blocked.push(standup_line(opt, &p.title, i));
+            } else {
+                estimate += i.estimate.unwrap_or(0.0);
+                in_progress.push(standup_line(opt, &p.title, i));
             }
-            if let Some(by_pipeline_name) = filter.by_pipeline_name.to_owned().take() {
-                if let Some(pipeline) = x.clone().pipeline.take() {
-                    m = m && (pipeline.name == by_pipeline_name)
-                } else {
-                    m = false
-                }
+        }
+    }
+    let mut lines = vec![format!(
+        "{}: {} in progress ({}h), {} blocked",
+        login,
+        in_progress.len(),
+        format_estimate(opt, estimate),
+        blocked.len()
+    )];
+    lines.extend(in_progress);
+    if !blocked.is_empty() {
+        lines.push("Blocked:".to_string());
+        lines.extend(blocked);
+    }
+    lines
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ZenhubValidation {
+    check: String,
+    count: usize,
+    passed: bool,
+    /// `repo:number` of the failing issues
+    issues: Vec<String>,
+}
+
+/// Open issues of the board failing `check`
+fn validate_issues<'a>(
+    check: ValidateCheck,
+    pipelines: &'a [ZenhubPipelineInfo],
+    active: &[String],
+    stale_before: DateTime<Utc>,
+) -> Vec<&'a ZenhubIssueInfo> {
+    let mut failed = vec![];
+    for (n, p) in pipelines.iter().enumerate() {
+        let is_active = if active.is_empty() {
+            n > 0
+        } else {
+            active.iter().any(|a| a.eq_ignore_ascii_case(&p.title))
+        };
+        for x in p.list.iter().filter(|x| x.state != "closed") {
+            let fails = match check {
+                ValidateCheck::Unestimated => x.estimate.is_none(),
+                ValidateCheck::Unassigned => is_active && is_unassigned(x),
+                ValidateCheck::Stale => x
+                    .updated_at
+                    .as_deref()
+                    .and_then(parse_zenhub_datetime)
+                    .is_some_and(|updated| updated < stale_before),
+                ValidateCheck::NoMilestone => x.milestone.is_none(),
+            };
+            if fails {
+                failed.push(x);
+            }
+        }
+    }
+    failed
+}
+
+async fn run_validate(mut opt: Opt, checks: Vec<ValidateCheck>) -> Result<(), Box<dyn Error>> {
+    let config = read_config(&opt)?.validate;
+    let checks = match (checks.is_empty(), &config.checks) {
+        (false, _) => checks,
+        (true, Some(names)) => names
+            .iter()
+            .map(|name| name.parse())
+            .collect::<Result<Vec<ValidateCheck>, String>>()?,
+        (true, None) => ValidateCheck::ALL.to_vec(),
+    };
+    let stale_after = parse_age(config.stale_after.as_deref().unwrap_or("14d"))?;
+    if opt.assignee.is_none() && opt.team.is_empty() {
+        opt.all_assignees = true;
+    }
+    opt.all_pipelines = true;
+    let pipelines = read_board_issues(&opt).await?;
+    let stale_before = Utc::now() - stale_after;
+    let color = use_color(&opt);
+    let mut rows = vec![];
+    let mut details = vec![];
+    for check in checks {
+        let failed = validate_issues(check, &pipelines, &config.active_pipelines, stale_before);
+        rows.push(ZenhubValidation {
+            check: check.name().to_string(),
+            count: failed.len(),
+            passed: failed.is_empty(),
+            issues: failed
+                .iter()
+                .map(|x| format!("{}:{}", repo_display(&opt, x), x.issue_number))
+                .collect(),
+        });
+        details.push(failed);
+    }
+    let passed = rows.iter().all(|r| r.passed);
+    if opt.output.is_delimited() {
+        // a row per violation
+        if opt.output == OutputFormat::Csv && !opt.no_header {
+            let header = ["check", "issue", "pipeline", "title"];
+            println!("{}", delimited_row(&opt, &header));
+        }
+        for (r, failed) in rows.iter().zip(details) {
+            for x in failed {
+                let row = [
+                    r.check.as_str(),
+                    &format!("{}:{}", repo_display(&opt, x), x.issue_number),
+                    x.pipeline.as_ref().map_or("", |p| p.name.as_str()),
+                    x.title.trim(),
+                ];
+                println!("{}", delimited_row(&opt, &row));
+            }
+        }
+    } else if opt.output != OutputFormat::Text {
+        print_data(&opt, &rows)?;
+    } else {
+        println!("Check\tIssues\tResult");
+        for r in &rows {
+            let result = if r.passed { "ok" } else { "FAIL" };
+            println!("{}\t{}\t{}", r.check, r.count, result);
+        }
+        for (r, failed) in rows.iter().zip(details) {
+            if failed.is_empty() {
+                continue;
+            }
+            println!("## -- {} (count: {})", r.check, r.count);
+            for x in failed {
+                println!("{}", issue_row(&opt, color, x));
+            }
+        }
+    }
+    if !passed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn run_pipelines(opt: Opt) -> Result<(), Box<dyn Error>> {
+    if opt.pipeline.is_empty() && !opt.all_pipelines {
+        // only the user banner is printed, nothing to fail for
+        if let Err(e) = resolve_assignee(&opt).await {
+            eprintln!("Warning: {}", e);
+        }
+        return Ok(());
+    }
+    if opt.watch.is_some() {
+        return run_watch(opt, true).await;
+    }
+    let list = read_board_issues(&opt).await?;
+    render_pipelines(&opt, list)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::io::BufRead;
+    use std::sync::{Arc, Mutex};
+
+    fn test_opt(args: &[&str]) -> Opt {
+        let mut argv = vec!["zenhub", "--api-token", "token", "--workspace-id", "W"];
+        argv.extend_from_slice(args);
+        Opt::from_iter(argv)
+    }
+
+    /// HTTP server on a free local port answering every request with `respond(method, path)`.
+    /// Returns its root URL and `METHOD path` of the requests received
+    fn mock_server(respond: fn(&str, &str) -> (u16, String)) -> (String, Arc<Mutex<Vec<String>>>) {
+        mock_server_with(move |method, path, _| {
+            let (status, text) = respond(method, path);
+            (status, vec![], text)
+        })
+    }
+
+    /// `mock_server` answering with `respond(method, path, request headers)` its status,
+    /// extra response headers and body. Headers are `name: value` lines, names lowercased.
+    /// Each request is served by its own thread, so that concurrent requests overlap
+    fn mock_server_with<F>(respond: F) -> (String, Arc<Mutex<Vec<String>>>)
+    where
+        F: Fn(&str, &str, &[String]) -> (u16, Vec<String>, String) + Send + Sync + 'static,
+    {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let root = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(vec![]));
+        let log = received.clone();
+        let respond = Arc::new(respond);
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let (log, respond) = (log.clone(), respond.clone());
+                std::thread::spawn(move || {
+                    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let mut parts = line.split_whitespace();
+                    let method = parts.next().unwrap_or("").to_string();
+                    let path = parts.next().unwrap_or("").to_string();
+                    let mut headers = vec![];
+                    let mut length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        let (name, value) = header.split_once(':').unwrap_or((&header, ""));
+                        let (name, value) = (name.trim().to_lowercase(), value.trim());
+                        if name == "content-length" {
+                            length = value.parse().unwrap_or(0);
+                        }
+                        headers.push(format!("{}: {}", name, value));
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    log.lock().unwrap().push(format!("{} {}", method, path));
+                    let (status, extra, text) = respond(&method, &path, &headers);
+                    let mut head = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n",
+                        status,
+                        text.len()
+                    );
+                    for header in extra {
+                        head.push_str(&header);
+                        head.push_str("\r\n");
+                    }
+                    write!(stream, "{}\r\n{}", head, text).unwrap();
+                });
             }
-            if m {
-                if let Some(estimate_val) = x.clone().estimate.take() {
-                    estimate += estimate_val;
+        });
+        (root, received)
+    }
+
+    #[tokio::test]
+    async fn move_retry_skips_move_applied_by_failed_attempt() {
+        let (root, received) = mock_server(|method, _| match method {
+            "POST" => (500, r#"{"message":"boom"}"#.to_string()),
+            _ => (
+                200,
+                r#"{"estimate":null,"is_epic":false,"pipelines":[
+                    {"name":"Review","pipeline_id":"p-review","workspace_id":"W"}]}"#
+                    .to_string(),
+            ),
+        });
+        let mut opt = test_opt(&[]);
+        opt.api_root = root;
+        let moved = move_issue_with_retry(opt, 101, 2, "p-review")
+            .await
+            .unwrap();
+        assert!(!moved);
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                "POST /p2/workspaces/W/repositories/101/issues/2/moves",
+                "GET /p1/repositories/101/issues/2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_caps_concurrency_and_collects_failures() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let opt = test_opt(&["--concurrency", "3"]);
+        let items: Vec<u64> = (1..=10).collect();
+        let (running, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let op = |n: &u64| {
+            let n = *n;
+            let (running, peak) = (&running, &peak);
+            async move {
+                peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::delay_for(std::time::Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if n.is_multiple_of(2) {
+                    Err(format!("even {}", n).into())
                 } else {
-                    not_estimated += 1;
+                    Ok(n * 10)
                 }
             }
-            m
+        };
+        let mut reported = vec![];
+        let report = |n: &u64, outcome: BatchResult<u64>| {
+            reported.push((*n, outcome.map_err(|e| e.to_string())));
+        };
+        let failed = run_batch(&opt, &items, op, report).await;
+        assert_eq!(failed, 5);
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(reported.len(), 10);
+        assert_eq!(reported[0], (1, Ok(10)));
+        assert_eq!(reported[1], (2, Err("even 2".to_string())));
+        let order: Vec<u64> = reported.iter().map(|(n, _)| *n).collect();
+        assert_eq!(order, items);
+    }
+
+    #[tokio::test]
+    async fn move_from_file_caps_concurrent_requests() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (in_flight, max) = (running.clone(), peak.clone());
+        let (root, received) = mock_server_with(move |method, path, _| {
+            let text = match (method, path) {
+                ("POST", "/v1/graphql") => {
+                    include_str!("../tests/fixtures/post_v1_graphql_WorkspaceRepositories.json")
+                }
+                ("GET", "/v5/workspaces/W/board") => {
+                    include_str!("../tests/fixtures/v5_workspaces_W_board.json")
+                }
+                _ => {
+                    max.fetch_max(
+                        in_flight.fetch_add(1, Ordering::SeqCst) + 1,
+                        Ordering::SeqCst,
+                    );
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    "{}"
+                }
+            };
+            (200, vec![], text.to_string())
+        });
+        let path = std::env::temp_dir().join(format!("zenhub-moves-{}.csv", std::process::id()));
+        let lines: Vec<String> = (1..=8).map(|n| format!("api:{},Review", n)).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let mut opt = test_opt(&[
+            "--concurrency",
+            "3",
+            "--rate",
+            "6000",
+            "--yes",
+            "--no-audit",
+        ]);
+        opt.api_root = root;
+        run_move(opt, None, None, Some(path.clone())).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let moves = received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.ends_with("/moves"))
+            .count();
+        assert_eq!(moves, 8);
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_spaces_requests() {
+        let mut limiter = RateLimiter::new(600);
+        let started = std::time::Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        // the first request goes at once, the next two wait 100ms each
+        assert!(started.elapsed() >= std::time::Duration::from_millis(190));
+        assert_eq!(limiter.requests, 3);
+    }
+
+    fn redirect_request(opt: &Opt, method: reqwest::Method, body: Option<&str>) -> ZenhubRequest {
+        let mut headers = zenhub_headers(opt.clone());
+        headers.insert(reqwest::header::AUTHORIZATION, "token gh".parse().unwrap());
+        headers.insert(reqwest::header::COOKIE, "session=1".parse().unwrap());
+        ZenhubRequest {
+            method,
+            url: reqwest::Url::parse("https://api.zenhub.com/v5/workspaces/W/board").unwrap(),
+            headers,
+            body: body.map(|b| b.to_string()),
+        }
+    }
+
+    fn location(to: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::LOCATION, to.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn redirect_within_origin_keeps_credentials() {
+        let opt = test_opt(&["--header", "X-Proxy-Key: secret"]);
+        let mut request = redirect_request(&opt, reqwest::Method::GET, None);
+        let status = reqwest::StatusCode::FOUND;
+        assert!(follow_redirect(&opt, 0, status, &location("/v5/board"), &mut request).unwrap());
+        assert_eq!(request.url.as_str(), "https://api.zenhub.com/v5/board");
+        assert_eq!(request.headers["X-Authentication-Token"], "token");
+        assert_eq!(request.headers["X-Proxy-Key"], "secret");
+        assert!(request.headers.contains_key(reqwest::header::AUTHORIZATION));
+    }
+
+    #[test]
+    fn redirect_to_other_origin_drops_credentials() {
+        let opt = test_opt(&["--header", "X-Proxy-Key: secret"]);
+        let mut request = redirect_request(&opt, reqwest::Method::GET, None);
+        let to = location("https://cdn.example.com/board");
+        let status = reqwest::StatusCode::FOUND;
+        assert!(follow_redirect(&opt, 0, status, &to, &mut request).unwrap());
+        assert_eq!(request.url.as_str(), "https://cdn.example.com/board");
+        for name in [
+            "X-Authentication-Token",
+            "Authorization",
+            "Cookie",
+            "X-Proxy-Key",
+        ] {
+            assert!(!request.headers.contains_key(name), "{} was kept", name);
+        }
+        assert!(request.headers.contains_key("X-Zenhub-Agent"));
+    }
+
+    #[test]
+    fn redirect_303_turns_post_into_get() {
+        let opt = test_opt(&[]);
+        let mut request = redirect_request(&opt, reqwest::Method::POST, Some("{}"));
+        let status = reqwest::StatusCode::SEE_OTHER;
+        assert!(follow_redirect(&opt, 0, status, &location("/done"), &mut request).unwrap());
+        assert_eq!(request.method, reqwest::Method::GET);
+        assert_eq!(request.body, None);
+    }
+
+    #[test]
+    fn redirect_307_keeps_method_and_body() {
+        let opt = test_opt(&[]);
+        let mut request = redirect_request(&opt, reqwest::Method::POST, Some("{}"));
+        let status = reqwest::StatusCode::TEMPORARY_REDIRECT;
+        assert!(follow_redirect(&opt, 0, status, &location("/again"), &mut request).unwrap());
+        assert_eq!(request.method, reqwest::Method::POST);
+        assert_eq!(request.body.as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn redirects_are_limited() {
+        let opt = test_opt(&[]);
+        let mut request = redirect_request(&opt, reqwest::Method::GET, None);
+        let status = reqwest::StatusCode::FOUND;
+        let to = location("/loop");
+        assert!(follow_redirect(&opt, MAX_REDIRECTS - 1, status, &to, &mut request).unwrap());
+        let e = follow_redirect(&opt, MAX_REDIRECTS, status, &to, &mut request).unwrap_err();
+        assert!(e.starts_with("too many redirects"), "{}", e);
+        let ok = reqwest::StatusCode::OK;
+        assert!(!follow_redirect(&opt, 0, ok, &to, &mut request).unwrap());
+    }
+
+    /// Server redirecting `/hop/n` to `/hop/n+1` for ever
+    fn redirect_loop() -> (String, Arc<Mutex<Vec<String>>>) {
+        mock_server_with(|_, path, _| {
+            let n: usize = path.trim_start_matches("/hop/").parse().unwrap_or(0);
+            (
+                302,
+                vec![format!("Location: /hop/{}", n + 1)],
+                String::new(),
+            )
         })
-        .collect::<Vec<ZenhubIssueInfo>>();
-    let mut title: String = "Issues".to_string();
-    if let Some(pipeline_name) = filter.by_pipeline_name.to_owned().take() {
-        title = pipeline_name;
-    }
-    Ok(ZenhubPipelineInfo {
-        title: title,
-        list: filtered,
-        estimate: estimate,
-        not_estimated: not_estimated,
-    })
-}
+    }
 
-#[allow(dead_code)]
-async fn read_repositories(opt: Opt) -> Result<Vec<ZenhubRepository>, Box<dyn Error>> {
-    let url: String = format!("{}/v1/graphql", opt.api_root);
-    let payload = format!(
-        r###"{{"query":"{{
-        workspace(id: \"{}\") {{
-            ...space
-        }}
-    }}
-    fragment space on Workspace {{
-        id
-        name
-        description
-        repositories {{
-            ghId
-            name
-            ownerName
-        }}
-    }}
-"}}"###,
-        opt.workspace_id
-    )
-    .replace('\n', "\\n");
-    // println!("url={}\n{}\n", url, payload);
+    #[tokio::test]
+    async fn fetch_stops_at_the_11th_hop() {
+        let (root, received) = redirect_loop();
+        let mut opt = test_opt(&[]);
+        opt.api_root = root;
+        let e = zenhub_fetch(opt, reqwest::Method::GET, "/hop/0", None)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().starts_with("too many redirects"), "{}", e);
+        // the request and the 10 redirects followed
+        assert_eq!(received.lock().unwrap().len(), MAX_REDIRECTS + 1);
+    }
 
-    let r = reqwest::Client::new()
-        .post(&url)
-        .headers(zenhub_headers(opt))
-        .body(payload)
-        .send()
-        .await?
-        .json::<ZenhubRepositoriesResponse>()
-        .await?;
-    // println!("{:#?}", r.data.workspace.repositories);
-    Ok(r.data.workspace.repositories)
-}
+    #[test]
+    fn reader_stops_at_the_11th_hop() {
+        let (root, received) = redirect_loop();
+        let mut opt = test_opt(&[]);
+        opt.api_root = root;
+        let e = zenhub_reader(&opt, "/hop/0").err().unwrap();
+        assert!(e.to_string().starts_with("too many redirects"), "{}", e);
+        assert_eq!(received.lock().unwrap().len(), MAX_REDIRECTS + 1);
+    }
 
-fn display_issues(pipeline: ZenhubPipelineInfo) {
-    println!(
-        "## -- {} (estimate: {}, not estimated: {})",
-        pipeline.title, pipeline.estimate, pipeline.not_estimated
-    );
-    for i in pipeline.list {
-        let mut estimate_str: String = "".to_string();
-        if let Some(est) = i.clone().estimate.take() {
-            estimate_str = format!("{}", est);
+    /// Headers received by a server on another origin, which `/board` of the first one
+    /// redirects to with `status`, and the headers received by the first one
+    fn cross_origin_headers(status: u16, send: impl FnOnce(Opt)) -> (Vec<String>, Vec<String>) {
+        let other_headers = Arc::new(Mutex::new(vec![]));
+        let log = other_headers.clone();
+        let (other, _) = mock_server_with(move |_, _, headers| {
+            log.lock().unwrap().extend_from_slice(headers);
+            (200, vec![], "{}".to_string())
+        });
+        let first_headers = Arc::new(Mutex::new(vec![]));
+        let log = first_headers.clone();
+        let (root, _) = mock_server_with(move |_, _, headers| {
+            log.lock().unwrap().extend_from_slice(headers);
+            (
+                status,
+                vec![format!("Location: {}/board", other)],
+                String::new(),
+            )
+        });
+        let mut opt = test_opt(&["--header", "X-Proxy-Key: secret"]);
+        opt.api_root = root;
+        send(opt);
+        let first = first_headers.lock().unwrap().clone();
+        let other = other_headers.lock().unwrap().clone();
+        (first, other)
+    }
+
+    fn assert_no_credentials_cross_origin((first, other): (Vec<String>, Vec<String>)) {
+        assert!(first.contains(&"x-authentication-token: token".to_string()));
+        assert!(first.contains(&"x-proxy-key: secret".to_string()));
+        assert!(other.iter().any(|h| h.starts_with("x-zenhub-agent:")));
+        for h in &other {
+            assert!(
+                !h.starts_with("x-authentication-token:") && !h.starts_with("x-proxy-key:"),
+                "{} was sent to the other origin",
+                h
+            );
         }
-        println!(
-            "{}:{}\t{}h\t{}\t{}",
-            i.repo_name,
-            i.issue_number,
-            estimate_str,
-            i.state,
-            i.title.trim(),
-        )
     }
-}
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let opt = Opt::from_args();
-    //    println!("Options {:#?}", opt);
+    #[test]
+    fn fetch_sends_no_credentials_to_other_origin() {
+        for status in [301, 302] {
+            assert_no_credentials_cross_origin(cross_origin_headers(status, |opt| {
+                let mut runtime = tokio::runtime::Runtime::new().unwrap();
+                let text = runtime
+                    .block_on(zenhub_fetch(opt, reqwest::Method::GET, "/board", None))
+                    .unwrap();
+                assert_eq!(text, "{}");
+            }));
+        }
+    }
 
-    let resp_user = read_user(opt.clone()).await.unwrap();
-    println!(
-        "User\t{}\t{}",
-        resp_user.github.username, resp_user.github.email
-    );
+    #[test]
+    fn reader_sends_no_credentials_to_other_origin() {
+        for status in [301, 302] {
+            assert_no_credentials_cross_origin(cross_origin_headers(status, |opt| {
+                let mut text = String::new();
+                zenhub_reader(&opt, "/board")
+                    .unwrap()
+                    .read_to_string(&mut text)
+                    .unwrap();
+                assert_eq!(text, "{}");
+            }));
+        }
+    }
+
+    #[test]
+    fn verbose_log_redacts_credentials_and_extra_headers() {
+        let opt = test_opt(&[
+            "--header",
+            "X-Proxy-Key: secret",
+            "--header",
+            "X-Team: core",
+        ]);
+        let mut headers = zenhub_headers(opt.clone());
+        headers.insert(reqwest::header::AUTHORIZATION, "token gh".parse().unwrap());
+        let lines = request_log_lines(
+            &opt,
+            &reqwest::Method::POST,
+            "/v1/graphql",
+            &headers,
+            Some("{}"),
+        );
+        assert_eq!(lines[0], "> POST /v1/graphql");
+        for line in [
+            "> x-authentication-token: <redacted>",
+            "> authorization: <redacted>",
+            "> x-proxy-key: <redacted>",
+            "> x-team: <redacted>",
+            "> content-type: application/json",
+            "> {}",
+        ] {
+            assert!(
+                lines.iter().any(|l| l == line),
+                "no {:?} in {:?}",
+                line,
+                lines
+            );
+        }
+        assert!(!lines
+            .iter()
+            .any(|l| l.contains("secret") || l.contains("core")));
+    }
 
-    let repositories = read_repositories(opt.clone()).await.unwrap();
-    let username = Some(resp_user.github.username);
+    #[test]
+    fn repo_ids_are_sorted_and_deduplicated() {
+        let repositories: Vec<ZenhubRepository> = [103, 7, 101, 7]
+            .iter()
+            .map(|&gh_id| ZenhubRepository {
+                gh_id,
+                name: String::new(),
+                owner_name: String::new(),
+            })
+            .collect();
+        assert_eq!(repo_ids_param(&repositories), "7,101,103");
+        assert_eq!(repo_ids_param(&[]), "");
+    }
 
-    if opt.clone().estimate > 0.0 {}
+    #[test]
+    fn empty_pipeline_says_no_issues() {
+        let opt = test_opt(&[]);
+        let pipeline = pipeline_info(&opt, "Review".to_string(), vec![]);
+        assert_eq!(
+            pipeline_lines(&opt, false, &pipeline),
+            vec![
+                "## -- Review (count: 0, estimate: 0, not estimated: 0)",
+                "(no issues)",
+            ]
+        );
+        let opt = test_opt(&["--no-header"]);
+        assert!(pipeline_lines(&opt, false, &pipeline).is_empty());
+    }
 
-    if !opt.clone().set.is_empty() {}
+    /// Open issue of `repo`, with `parent_epics` given as `repo_id, issue_number`
+    fn issue(
+        repo: &str,
+        number: u64,
+        estimate: Option<f32>,
+        parents: &[(u64, u64)],
+    ) -> ZenhubIssueInfo {
+        serde_json::from_value(serde_json::json!({
+            "assignee": null,
+            "assignees": [],
+            "created_at": "2026-09-01T10:00:00Z",
+            "estimate": estimate,
+            "html_url": format!("https://github.com/acme/{}/issues/{}", repo, number),
+            "is_epic": false,
+            "labels": [],
+            "repo_name": repo,
+            "parent_epics": parents
+                .iter()
+                .map(|(repo_id, issue_number)| {
+                    serde_json::json!({ "repo_id": repo_id, "issue_number": issue_number })
+                })
+                .collect::<Vec<_>>(),
+            "state": "open",
+            "title": format!("Issue {}", number),
+            "issue_number": number,
+        }))
+        .unwrap()
+    }
 
-    let pipelines = opt.clone().pipeline;
-    for p in pipelines {
-        display_issues(
-            read_issues(
-                opt.clone(),
-                repositories.clone(),
-                &ZenhubIssuesFilter {
-                    by_assignee: username.clone(),
-                    by_pipeline_name: Some(p),
-                },
+    #[tokio::test]
+    async fn tree_nests_issues_of_unnamed_repo_id() {
+        let opt = test_opt(&["--repo-id", "100"]);
+        let repositories = read_issue_repositories(&opt).await.unwrap();
+        let mut epic = issue("api", 5, None, &[]);
+        epic.is_epic = true;
+        let mut list = vec![epic, issue("api", 6, Some(2.0), &[(100, 5)])];
+        fill_repo_ids(&repositories, &mut list);
+        assert_eq!(list[1].repo_id, Some(100));
+        assert_eq!(
+            issue_tree_lines(&opt, false, &list),
+            vec!["api:5\th\topen\tIssue 5", "└── api:6\t2h\topen\tIssue 6"]
+        );
+    }
+
+    #[test]
+    fn estimate_filters_see_rounded_estimate() {
+        let opt = test_opt(&["--round", "int", "--min-estimate", "3"]);
+        let filter = ZenhubIssuesFilter::from_opt(&opt, None, None);
+        let mut x = issue("api", 2, Some(2.6), &[]);
+        filter.derive_estimate(&mut x);
+        assert_eq!(x.estimate, Some(3.0));
+        assert!(filter.matches(&x));
+    }
+
+    fn v5_gone(_: &str, path: &str) -> (u16, String) {
+        if path.starts_with("/v5/") {
+            (404, r#"{"message":"Not Found"}"#.to_string())
+        } else {
+            let board = r#"{"pipelines":[{"id":"p-review","name":"Review","issues":[
+                {"issue_number":4,"estimate":{"value":8},"is_epic":false}]}]}"#;
+            (200, board.to_string())
+        }
+    }
+
+    fn v5_rejects_token(_: &str, _: &str) -> (u16, String) {
+        (401, r#"{"message":"Invalid token"}"#.to_string())
+    }
+
+    #[tokio::test]
+    async fn auto_falls_back_to_v4_only_when_v5_is_gone() {
+        for (respond, falls_back) in [
+            (v5_gone as fn(&str, &str) -> (u16, String), true),
+            (v5_rejects_token, false),
+        ] {
+            let (root, received) = mock_server(respond);
+            let mut opt = test_opt(&["--repo-id", "102"]);
+            opt.api_root = root;
+            opt.github_token = None;
+            let repositories = read_issue_repositories(&opt).await.unwrap();
+            let filter = ZenhubIssuesFilter::default();
+            let res = read_issues(opt, repositories, &filter).await;
+            assert_eq!(res.is_ok(), falls_back);
+            if falls_back {
+                let list = res.unwrap().list;
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0].estimate, Some(8.0));
+                // no GitHub lookups without a token
+                assert_eq!(received.lock().unwrap().len(), 2);
+            }
+        }
+    }
+
+    #[test]
+    fn validate_counts_issues_with_neither_assignee_field() {
+        let opt = test_opt(&[]);
+        let mut legacy = issue("api", 1, None, &[]);
+        legacy.assignee = Some(ZenhubAssignee {
+            html_url: None,
+            avatar_url: None,
+            login: "alice".to_string(),
+            id: 1,
+        });
+        let pipelines = vec![
+            pipeline_info(
+                &opt,
+                "Backlog".to_string(),
+                vec![issue("api", 3, None, &[])],
+            ),
+            pipeline_info(
+                &opt,
+                "Review".to_string(),
+                vec![legacy, issue("api", 2, None, &[])],
+            ),
+        ];
+        let failed = validate_issues(ValidateCheck::Unassigned, &pipelines, &[], Utc::now());
+        let numbers: Vec<u64> = failed.iter().map(|x| x.issue_number).collect();
+        assert_eq!(numbers, vec![2]);
+    }
+
+    #[test]
+    fn zenhub_datetimes_in_any_format() {
+        let expected = Utc.with_ymd_and_hms(2026, 9, 1, 10, 30, 0).unwrap();
+        for s in &[
+            "2026-09-01T10:30:00Z",
+            "2026-09-01T12:30:00+02:00",
+            "2026-09-01T10:30:00.000+0000",
+            "2026-09-01 10:30:00+0000",
+            "2026-09-01T10:30:00.000",
+            " 2026-09-01 10:30:00 ",
+        ] {
+            assert_eq!(parse_zenhub_datetime(s), Some(expected), "{}", s);
+        }
+        assert_eq!(
+            parse_zenhub_datetime("2026-09-01"),
+            Some(Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(parse_zenhub_datetime("yesterday"), None);
+        assert_eq!(parse_zenhub_datetime(""), None);
+    }
+
+    #[test]
+    fn issue_urls() {
+        for s in &[
+            "https://github.com/acme/api/issues/12",
+            "http://www.github.com/acme/api/pull/12",
+            "https://github.com/acme/api/issues/12#issuecomment-1",
+            "https://github.com/acme/api/issues/12?q=1",
+        ] {
+            let r = parse_issue_url(s).unwrap();
+            assert_eq!(r.owner_name.as_deref(), Some("acme"), "{}", s);
+            assert_eq!(r.repo_name, "api", "{}", s);
+            assert_eq!(r.issue_number, 12, "{}", s);
+        }
+        assert!(parse_issue_url("https://github.com/acme/api").is_err());
+        assert!(parse_issue_url("https://github.com/acme/api/issues/x").is_err());
+        assert!(parse_issue_url("https://github.com//api/issues/12").is_err());
+    }
+
+    #[test]
+    fn durations_in_two_largest_adjacent_units() {
+        let short = test_opt(&[]);
+        let long = test_opt(&["--duration-style", "long"]);
+        let d = |minutes| chrono::Duration::minutes(minutes);
+        assert_eq!(humanize_duration(&short, d(0)), "0m");
+        assert_eq!(humanize_duration(&short, d(5)), "5m");
+        assert_eq!(humanize_duration(&short, d(-5)), "5m");
+        assert_eq!(
+            humanize_duration(&short, d((2 * 24 + 4) * 60 + 10)),
+            "2d 4h"
+        );
+        assert_eq!(humanize_duration(&short, d(21 * 24 * 60)), "3w");
+        assert_eq!(humanize_duration(&short, d((14 * 24 + 3) * 60)), "2w");
+        assert_eq!(humanize_duration(&long, d(0)), "0 minutes");
+        assert_eq!(humanize_duration(&long, d(24 * 60 + 60)), "1 day 1 hour");
+        assert_eq!(
+            humanize_duration(&long, d(2 * 60 + 30)),
+            "2 hours 30 minutes"
+        );
+    }
+
+    #[test]
+    fn delimited_fields_are_escaped() {
+        assert_eq!(csv_field("plain", ','), "plain");
+        assert_eq!(csv_field("a,b", ','), "\"a,b\"");
+        assert_eq!(csv_field("a,b", '\t'), "a,b");
+        assert_eq!(csv_field("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines", ','), "\"two\nlines\"");
+        assert_eq!(tsv_field("plain, text"), "plain, text");
+        assert_eq!(tsv_field("a\tb\nc\r\\d"), "a\\tb\\nc\\r\\\\d");
+    }
+
+    #[tokio::test]
+    async fn graph_names_issues_of_several_repo_ids() {
+        let (root, received) = mock_server(|_, _| {
+            (
+                200,
+                include_str!("../tests/fixtures/post_v1_graphql_WorkspaceRepositories.json")
+                    .to_string(),
             )
-            .await?,
+        });
+        let mut opt = test_opt(&["--repo-id", "101", "--repo-id", "102"]);
+        opt.api_root = root;
+        let repositories = read_issue_repositories(&opt).await.unwrap();
+        let mut list = vec![issue("api", 1, None, &[]), issue("web", 4, None, &[])];
+        fill_repo_ids(&repositories, &mut list);
+        assert_eq!(list[0].repo_id, None);
+        graph_repo_ids(&opt, &repositories, &mut list).await;
+        assert_eq!(list[0].repo_id, Some(101));
+        assert_eq!(list[1].repo_id, Some(102));
+        assert_eq!(*received.lock().unwrap(), vec!["POST /v1/graphql"]);
+    }
+
+    #[tokio::test]
+    async fn repo_ids_are_not_looked_up() {
+        let (root, received) = mock_server(|_, _| (200, "[]".to_string()));
+        let mut opt = test_opt(&["--all-assignees", "--repo-id", "1", "--repo-id", "2"]);
+        opt.api_root = root;
+        let repositories = read_issue_repositories(&opt).await.unwrap();
+        let filter = ZenhubIssuesFilter::from_opt(&opt, None, None);
+        read_issues(opt, repositories, &filter).await.unwrap();
+        let received = received.lock().unwrap();
+        assert!(!received.iter().any(|r| r.starts_with("POST /v1/graphql")));
+        assert_eq!(received.len(), 1);
+        assert!(received[0].starts_with("GET /v5/workspaces/W/issues?repo_ids=1,2&"));
+    }
+
+    /// Issue of `api` assigned to `logins`, the first one is the `assignee`
+    fn assigned_issue(number: u64, logins: &[&str]) -> ZenhubIssueInfo {
+        let mut x = issue("api", number, None, &[]);
+        let assignees: Vec<ZenhubAssignee> = logins
+            .iter()
+            .map(|login| {
+                serde_json::from_value(serde_json::json!({ "login": login, "id": 1 })).unwrap()
+            })
+            .collect();
+        x.assignee = assignees.first().cloned();
+        x.assignees = assignees;
+        x
+    }
+
+    #[test]
+    fn assignee_file_matches_any_assignee() {
+        let mut filter = ZenhubIssuesFilter::from_opt(&test_opt(&["--all-assignees"]), None, None);
+        filter.by_assignees = vec!["bob".to_string()];
+        assert!(filter.matches_except_pipeline(&assigned_issue(1, &["alice", "bob"])));
+        assert!(filter.matches_except_pipeline(&assigned_issue(2, &["bob"])));
+        assert!(!filter.matches_except_pipeline(&assigned_issue(3, &["alice"])));
+        assert!(!filter.matches_except_pipeline(&assigned_issue(4, &[])));
+    }
+
+    #[test]
+    fn assignee_matches_any_assignee_and_unassigned_neither() {
+        let opt = test_opt(&["--assignee", "bob"]);
+        let filter = ZenhubIssuesFilter::from_opt(&opt, Some("bob".to_string()), None);
+        assert!(filter.matches_except_pipeline(&assigned_issue(1, &["alice", "bob"])));
+        assert!(!filter.matches_except_pipeline(&assigned_issue(2, &["alice"])));
+        let mut only_assignees = assigned_issue(3, &["bob"]);
+        only_assignees.assignee = None;
+        assert!(filter.matches_except_pipeline(&only_assignees));
+
+        let opt = test_opt(&["--unassigned"]);
+        let filter = ZenhubIssuesFilter::from_opt(&opt, None, None);
+        assert!(filter.matches_except_pipeline(&assigned_issue(4, &[])));
+        assert!(!filter.matches_except_pipeline(&only_assignees));
+        let mut only_assignee = assigned_issue(5, &["bob"]);
+        only_assignee.assignees.clear();
+        assert!(!filter.matches_except_pipeline(&only_assignee));
+    }
+
+    #[test]
+    fn velocity_as_csv() {
+        let mut closed = issue("api", 1, Some(3.0), &[]);
+        closed.state = "closed".to_string();
+        closed.closed_at = Some("2026-10-06T12:00:00Z".to_string());
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let opt = test_opt(&["--output", "csv"]);
+        let (rows, _) = velocity_weeks(&opt, &[closed], 2, today);
+        assert_eq!(
+            velocity_delimited_lines(&opt, &rows),
+            vec!["week,closed,estimate", "2026-10-05,1,3", "2026-10-12,0,0"]
+        );
+        let opt = test_opt(&["--output", "csv", "--delimiter", ";", "--no-header"]);
+        assert_eq!(
+            velocity_delimited_lines(&opt, &rows),
+            vec!["2026-10-05;1;3", "2026-10-12;0;0"]
+        );
+    }
+
+    #[test]
+    fn watch_delta_follows_renamed_repositories() {
+        let opt = test_opt(&["--watch", "60", "--delta", "--color", "never"]);
+        let mut before = issue("api", 1, Some(2.0), &[]);
+        before.repo_id = Some(101);
+        let mut after = before.clone();
+        after.repo_name = "api-v2".to_string();
+        after.estimate = Some(3.0);
+        let mut unnamed = issue("web", 4, None, &[]);
+        unnamed.repo_id = None;
+        let prev = watch_snapshot(
+            &opt,
+            &[pipeline_info(
+                &opt,
+                "Backlog".to_string(),
+                vec![before, unnamed.clone()],
+            )],
+        );
+        let next = watch_snapshot(
+            &opt,
+            &[pipeline_info(
+                &opt,
+                "Backlog".to_string(),
+                vec![after, unnamed],
+            )],
+        );
+        let lines = watch_changes(&opt, &prev, &next);
+        assert_eq!(lines.len(), 1, "{:?}", lines);
+        assert!(
+            lines[0].ends_with("\testimated\tapi-v2:1\t2 -> 3"),
+            "{}",
+            lines[0]
         );
     }
-    //    for repo in repositories {
-    //         println!("{}\t{}", repo.gh_id, repo.name);
-    //    }
 
-    // let board = read_pipelines(opt.clone()).await.unwrap();
-    // let pipelines = board
-    // .pipelines
-    // .iter()
-    // .map(|x| &x.name)
-    // .collect::<HashSet<_>>();
-    // println!("Pipelines\t{:#?}", pipelines);
+    #[test]
+    fn no_dotenv_is_a_flag_not_a_value() {
+        assert!(test_opt(&["--no-dotenv", "issues"]).no_dotenv);
+        assert!(test_opt(&["issues", "--no-dotenv"]).no_dotenv);
+        assert!(!test_opt(&["--title-contains=--no-dotenv"]).no_dotenv);
+        assert!(!test_opt(&["search", "--", "--no-dotenv"]).no_dotenv);
+        let args = |args: &[&str]| {
+            args.iter()
+                .map(std::ffi::OsString::from)
+                .collect::<Vec<_>>()
+        };
+        assert!(has_no_dotenv_flag(args(&["-v", "--no-dotenv"]).into_iter()));
+        assert!(!has_no_dotenv_flag(
+            args(&["--", "--no-dotenv"]).into_iter()
+        ));
+        assert!(!has_no_dotenv_flag(
+            args(&["comment", "api:1", "--no-dotenv"]).into_iter()
+        ));
+    }
 
-    Ok(())
+    #[test]
+    fn standup_skips_pipelines_marked_closed() {
+        let opt = test_opt(&["--assignee", "alice"]);
+        let board: ZenhubBoardResponse = serde_json::from_value(serde_json::json!({
+            "_id": "b1",
+            "name": "Board",
+            "pipelines": [
+                { "_id": "p1", "name": "In Progress", "description": null },
+                { "_id": "p2", "name": "Closed issues", "description": null },
+                { "_id": "p3", "name": "Done", "description": "Closed and shipped" },
+            ],
+        }))
+        .unwrap();
+        let closed: Vec<String> = board
+            .pipelines
+            .into_iter()
+            .filter(is_closed_pipeline)
+            .map(|p| p.name)
+            .collect();
+        let pipelines: Vec<ZenhubPipelineInfo> = ["In Progress", "Closed issues", "Done"]
+            .iter()
+            .enumerate()
+            .map(|(n, name)| {
+                let x = issue("api", n as u64 + 1, Some(1.0), &[]);
+                pipeline_info(&opt, name.to_string(), vec![x])
+            })
+            .collect();
+        let lines = standup_lines(&opt, "alice", &pipelines, &closed);
+        assert_eq!(lines[0], "alice: 1 in progress (1h), 0 blocked");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains("api:1"), "{}", lines[1]);
+    }
+
+    #[test]
+    fn titles_of_same_named_repositories_are_apart() {
+        let repositories: Vec<ZenhubRepository> = [(101, "acme"), (201, "other")]
+            .iter()
+            .map(|(id, owner)| ZenhubRepository {
+                gh_id: *id,
+                name: "api".to_string(),
+                owner_name: owner.to_string(),
+            })
+            .collect();
+        let mut list = vec![issue("api", 1, None, &[]), issue("api", 1, None, &[])];
+        list[1].html_url = "https://github.com/other/api/issues/1".to_string();
+        list[1].title = "Other title".to_string();
+        fill_repo_ids(&repositories, &mut list);
+        let titles = issue_titles(list);
+        assert_eq!(titles[&(101, 1)], "Issue 1");
+        assert_eq!(titles[&(201, 1)], "Other title");
+    }
+
+    #[tokio::test]
+    async fn warnings_are_collected_when_asked() {
+        let (root, _) = mock_server(|_, path| {
+            if path.starts_with("/v5/") {
+                (404, "{}".to_string())
+            } else {
+                (200, r#"{"pipelines":[]}"#.to_string())
+            }
+        });
+        let mut opt = test_opt(&["--all-assignees", "--repo-id", "1"]);
+        opt.api_root = root;
+        opt.github_token = None;
+        let warnings = Arc::new(Mutex::new(vec![]));
+        opt.warnings = Some(warnings.clone());
+        let repositories = read_issue_repositories(&opt).await.unwrap();
+        let filter = ZenhubIssuesFilter::from_opt(&opt, None, None);
+        read_issues(opt, repositories, &filter).await.unwrap();
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(
+            warnings[0].ends_with("falling back to v4"),
+            "{}",
+            warnings[0]
+        );
+        assert!(
+            warnings[1].starts_with("no GITHUB_TOKEN"),
+            "{}",
+            warnings[1]
+        );
+    }
 }
@@ -0,0 +1,404 @@
+//! `--select` expressions, evaluated per issue after the discrete filters
+//!
+//! Fields: `estimate`, `state`, `title`, `repo`, `org`, `number`, `pipeline`, `assignee`,
+//! `assignees`, `labels`, `milestone`, `is_epic`, `is_pr`, `author`, `created_at`,
+//! `updated_at`, `closed_at`, `url`.
+//! Literals: numbers, `"strings"`, `true`, `false`, `null`.
+//! Operators: `==` `!=` `<` `<=` `>` `>=`, `~=` (case-insensitive substring), `&&`, `||`, `!`,
+//! parentheses. String comparisons are case-insensitive, list fields (`labels`, `assignees`)
+//! match when any of their items does. Fields without value (`estimate` of unestimated
+//! issue) are `null`, ordering comparisons with `null` are false.
+
+use super::{is_pull_request, ZenhubIssueInfo};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Field(String),
+    Literal(Value),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Op, Box<Expr>, Box<Expr>),
+}
+
+const FIELDS: [&str; 18] = [
+    "estimate",
+    "state",
+    "title",
+    "repo",
+    "org",
+    "number",
+    "pipeline",
+    "assignee",
+    "assignees",
+    "labels",
+    "milestone",
+    "is_epic",
+    "is_pr",
+    "author",
+    "created_at",
+    "updated_at",
+    "closed_at",
+    "url",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Num(f64),
+    Str(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('~', Some('=')) => (Token::Op(Op::Contains), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('"', _) => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(format!("unterminated string at {}", i)),
+                        Some('"') => break,
+                        Some('\\') if j + 1 < chars.len() => {
+                            value.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(c) => {
+                            value.push(*c);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Str(value), j + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() || c == '.' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit() || **c == '.')
+                    .count();
+                let text: String = chars[i..i + len].iter().collect();
+                let value = text
+                    .parse()
+                    .map_err(|_| format!("invalid number {:?}", text))?;
+                (Token::Num(value), len)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_')
+                    .count();
+                (Token::Ident(chars[i..i + len].iter().collect()), len)
+            }
+            (c, _) => return Err(format!("unexpected {:?} at {}", c, i)),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.compare()
+    }
+
+    fn compare(&mut self) -> Result<Expr, String> {
+        let left = self.primary()?;
+        if let Some(Token::Op(op)) = self.peek().cloned() {
+            self.pos += 1;
+            let right = self.primary()?;
+            return Ok(Expr::Compare(op, Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("missing )".to_string()),
+                }
+            }
+            Some(Token::Num(n)) => Ok(Expr::Literal(Value::Num(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::Str(s))),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if FIELDS.contains(&name.as_str()) => Ok(Expr::Field(name)),
+                _ => Err(format!(
+                    "unknown field {:?}, expected one of: {}",
+                    name,
+                    FIELDS.join(", ")
+                )),
+            },
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Parses `--select` expression
+pub fn parse(s: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+    };
+    let expr = parser.or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {:?}", token)),
+    }
+}
+
+fn field(x: &ZenhubIssueInfo, name: &str) -> Value {
+    let opt_str = |s: Option<&String>| s.map_or(Value::Null, |s| Value::Str(s.clone()));
+    match name {
+        "estimate" => x.estimate.map_or(Value::Null, |e| Value::Num(f64::from(e))),
+        "state" => Value::Str(x.state.clone()),
+        "title" => Value::Str(x.title.trim().to_string()),
+        "repo" => Value::Str(x.repo_name.clone()),
+        "org" => opt_str(x.organization_name.as_ref()),
+        "number" => Value::Num(x.issue_number as f64),
+        "pipeline" => opt_str(x.pipeline.as_ref().map(|p| &p.name)),
+        "assignee" => opt_str(x.assignee.as_ref().map(|a| &a.login)),
+        "assignees" => Value::List(x.assignees.iter().map(|a| a.login.clone()).collect()),
+        "labels" => Value::List(x.labels.iter().map(|l| l.name.clone()).collect()),
+        "milestone" => opt_str(x.milestone.as_ref().map(|m| &m.title)),
+        "is_epic" => Value::Bool(x.is_epic),
+        "is_pr" => Value::Bool(is_pull_request(x)),
+        "author" => opt_str(x.user.as_ref().map(|u| &u.login)),
+        "created_at" => Value::Str(x.created_at.clone()),
+        "updated_at" => opt_str(x.updated_at.as_ref()),
+        "closed_at" => opt_str(x.closed_at.as_ref()),
+        "url" => Value::Str(x.html_url.clone()),
+        _ => Value::Null,
+    }
+}
+
+fn truthy(v: &Value) -> bool {
+    match v {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Num(n) => *n != 0.0,
+        Value::Str(s) => !s.is_empty(),
+        Value::List(l) => !l.is_empty(),
+    }
+}
+
+fn compare_scalar(op: Op, left: &Value, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Num(a), Value::Num(b)) => a.partial_cmp(b),
+        (Value::Str(a), Value::Str(b)) => {
+            if op == Op::Contains {
+                return a.to_lowercase().contains(&b.to_lowercase());
+            }
+            Some(a.to_lowercase().cmp(&b.to_lowercase()))
+        }
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Null, Value::Null) => Some(Ordering::Equal),
+        _ => None,
+    };
+    match (op, ordering) {
+        (Op::Eq, Some(o)) => o == Ordering::Equal,
+        (Op::Ne, Some(o)) => o != Ordering::Equal,
+        (Op::Ne, None) => true,
+        (Op::Lt, Some(o)) => o == Ordering::Less,
+        (Op::Le, Some(o)) => o != Ordering::Greater,
+        (Op::Gt, Some(o)) => o == Ordering::Greater,
+        (Op::Ge, Some(o)) => o != Ordering::Less,
+        _ => false,
+    }
+}
+
+fn compare(op: Op, left: &Value, right: &Value) -> bool {
+    let items = |l: &[String]| l.iter().map(|s| Value::Str(s.clone())).collect::<Vec<_>>();
+    match (left, right) {
+        (Value::List(l), v) if op == Op::Ne => {
+            !items(l).iter().any(|i| compare_scalar(Op::Eq, i, v))
+        }
+        (Value::List(l), v) => items(l).iter().any(|i| compare_scalar(op, i, v)),
+        (v, Value::List(l)) if op == Op::Ne => {
+            !items(l).iter().any(|i| compare_scalar(Op::Eq, v, i))
+        }
+        (v, Value::List(l)) => items(l).iter().any(|i| compare_scalar(op, v, i)),
+        _ => compare_scalar(op, left, right),
+    }
+}
+
+fn eval(expr: &Expr, x: &ZenhubIssueInfo) -> Value {
+    match expr {
+        Expr::Field(name) => field(x, name),
+        Expr::Literal(v) => v.clone(),
+        Expr::Not(e) => Value::Bool(!truthy(&eval(e, x))),
+        Expr::And(a, b) => Value::Bool(truthy(&eval(a, x)) && truthy(&eval(b, x))),
+        Expr::Or(a, b) => Value::Bool(truthy(&eval(a, x)) || truthy(&eval(b, x))),
+        Expr::Compare(op, a, b) => Value::Bool(compare(*op, &eval(a, x), &eval(b, x))),
+    }
+}
+
+impl Expr {
+    pub fn matches(&self, x: &ZenhubIssueInfo) -> bool {
+        truthy(&eval(self, x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(estimate: Option<f32>, labels: &[&str]) -> ZenhubIssueInfo {
+        serde_json::from_value(serde_json::json!({
+            "assignee": { "login": "alice", "id": 1 },
+            "assignees": [{ "login": "alice", "id": 1 }, { "login": "bob", "id": 2 }],
+            "created_at": "2026-09-01T10:00:00Z",
+            "estimate": estimate,
+            "html_url": "https://github.com/acme/api/issues/7",
+            "is_epic": false,
+            "labels": labels.iter().map(|name| serde_json::json!({ "name": name })).collect::<Vec<_>>(),
+            "parent_epics": [],
+            "repo_name": "api",
+            "state": "open",
+            "title": "Payment Form ",
+            "issue_number": 7,
+        }))
+        .unwrap()
+    }
+
+    fn matches(s: &str, x: &ZenhubIssueInfo) -> bool {
+        parse(s).unwrap().matches(x)
+    }
+
+    #[test]
+    fn parse_rejects_invalid_expressions() {
+        assert!(parse("estimate >").is_err());
+        assert!(parse("(estimate > 2").is_err());
+        assert!(parse("estimate > 2 2").is_err());
+        assert!(parse("title == \"open").is_err());
+        assert!(parse("unknown == 1").is_err());
+    }
+
+    #[test]
+    fn compares_numbers_and_strings() {
+        let x = issue(Some(3.0), &["bug"]);
+        assert!(matches("estimate >= 3 && number == 7", &x));
+        assert!(!matches("estimate > 3", &x));
+        assert!(matches("state == \"OPEN\" && repo != \"web\"", &x));
+        assert!(matches("title ~= \"payment\"", &x));
+        assert!(matches("title == \"payment form\"", &x));
+    }
+
+    #[test]
+    fn precedence_and_negation() {
+        let x = issue(Some(3.0), &[]);
+        assert!(matches("estimate > 5 || estimate < 5 && !is_epic", &x));
+        assert!(!matches("(estimate > 5 || estimate < 5) && is_epic", &x));
+        assert!(matches("!(estimate > 5)", &x));
+    }
+
+    #[test]
+    fn list_fields_match_any_item() {
+        let x = issue(None, &["bug", "ui"]);
+        assert!(matches("labels == \"UI\"", &x));
+        assert!(matches("assignees == \"bob\"", &x));
+        assert!(!matches("labels != \"bug\"", &x));
+        assert!(matches("labels != \"docs\"", &x));
+        assert!(!matches("labels", &issue(None, &[])));
+    }
+
+    #[test]
+    fn null_is_not_ordered() {
+        let x = issue(None, &[]);
+        assert!(matches("estimate == null", &x));
+        assert!(!matches("estimate < 1", &x));
+        assert!(!matches("estimate >= 0", &x));
+        assert!(matches("estimate != 1", &x));
+    }
+}
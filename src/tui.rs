@@ -0,0 +1,389 @@
+//! `zenhub tui` - pipelines as columns of issue cards, with moves and estimates
+
+use super::{
+    audit, filter_assignee, find_repo_id, format_estimate, move_issue_with_retry,
+    read_board_issues, read_pipelines, read_repositories, read_workspaces, repo_display,
+    set_estimate, IssueRef, Opt, ZenhubIssueInfo, ZenhubRepository, ZenhubWorkspace,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Margin};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+const HELP: &str = "←→ pipeline  ↑↓ issue  < > move issue  e estimate  w workspace  q quit";
+
+struct Column {
+    name: String,
+    id: String,
+    issues: Vec<ZenhubIssueInfo>,
+    state: ListState,
+}
+
+enum Mode {
+    Browse,
+    /// typing the estimate of the selected issue
+    Estimate(String),
+    /// picking the workspace to switch to
+    Workspaces(Vec<ZenhubWorkspace>, ListState),
+}
+
+/// Repositories and columns of a workspace
+struct Board {
+    repositories: Vec<ZenhubRepository>,
+    columns: Vec<Column>,
+}
+
+struct App {
+    opt: Opt,
+    repositories: Vec<ZenhubRepository>,
+    columns: Vec<Column>,
+    focus: usize,
+    mode: Mode,
+    status: String,
+    /// boards of the workspaces switched away from, by workspace id, so that switching
+    /// back shows them at once
+    boards: HashMap<String, Board>,
+}
+
+fn issue_ref(i: &ZenhubIssueInfo) -> IssueRef {
+    IssueRef {
+        owner_name: i.organization_name.clone(),
+        repo_name: i.repo_name.clone(),
+        issue_number: i.issue_number,
+    }
+}
+
+fn card<'a>(opt: &Opt, i: &'a ZenhubIssueInfo) -> ListItem<'a> {
+    let estimate_str = match i.estimate {
+        Some(est) => format!("  {}h", format_estimate(opt, est)),
+        None => String::new(),
+    };
+    ListItem::new(vec![
+        Line::from(format!(
+            "{}:{}{}",
+            repo_display(opt, i),
+            i.issue_number,
+            estimate_str
+        )),
+        Line::from(i.title.trim()),
+        Line::from(""),
+    ])
+}
+
+async fn load_board(opt: &Opt) -> Result<Board, Box<dyn Error>> {
+    let board = read_pipelines(opt.clone()).await?;
+    let repositories = read_repositories(opt.clone()).await?;
+    let columns = read_board_issues(opt)
+        .await?
+        .into_iter()
+        .filter_map(|p| {
+            let id = board
+                .pipelines
+                .iter()
+                .find(|x| x.name == p.title)?
+                ._id
+                .clone();
+            let mut state = ListState::default();
+            if !p.list.is_empty() {
+                state.select(Some(0));
+            }
+            Some(Column {
+                name: p.title,
+                id,
+                issues: p.list,
+                state,
+            })
+        })
+        .collect();
+    Ok(Board {
+        repositories,
+        columns,
+    })
+}
+
+impl App {
+    async fn load(opt: Opt) -> Result<Self, Box<dyn Error>> {
+        let board = load_board(&opt).await?;
+        Ok(App {
+            opt,
+            repositories: board.repositories,
+            columns: board.columns,
+            focus: 0,
+            mode: Mode::Browse,
+            status: String::new(),
+            boards: HashMap::new(),
+        })
+    }
+
+    async fn pick_workspace(&mut self) {
+        match read_workspaces(self.opt.clone()).await {
+            Ok(workspaces) if workspaces.is_empty() => self.status = "no workspaces".to_string(),
+            Ok(workspaces) => {
+                let mut state = ListState::default();
+                let current = workspaces
+                    .iter()
+                    .position(|w| w.id == self.opt.workspace_id);
+                state.select(Some(current.unwrap_or(0)));
+                self.mode = Mode::Workspaces(workspaces, state);
+            }
+            Err(e) => self.status = format!("cannot list workspaces: {}", e),
+        }
+    }
+
+    /// Shows the board of the workspace, loaded unless it was shown before
+    async fn switch_workspace(&mut self, workspace: ZenhubWorkspace) {
+        if workspace.id == self.opt.workspace_id {
+            return;
+        }
+        let mut opt = self.opt.clone();
+        opt.workspace_id = workspace.id.clone();
+        let board = match self.boards.remove(&workspace.id) {
+            Some(board) => board,
+            None => match load_board(&opt).await {
+                Ok(board) => board,
+                Err(e) => {
+                    self.status = format!("cannot load {}: {}", workspace.name, e);
+                    return;
+                }
+            },
+        };
+        let previous = Board {
+            repositories: std::mem::replace(&mut self.repositories, board.repositories),
+            columns: std::mem::replace(&mut self.columns, board.columns),
+        };
+        self.boards
+            .insert(std::mem::replace(&mut self.opt, opt).workspace_id, previous);
+        self.focus = 0;
+        self.status = format!("workspace {}", workspace.name);
+    }
+
+    /// Warnings of the requests since the last draw go to the status line
+    fn show_warnings(&mut self) {
+        let warnings = match &self.opt.warnings {
+            Some(warnings) => std::mem::take(&mut *warnings.lock().unwrap()),
+            None => return,
+        };
+        if !warnings.is_empty() {
+            self.status = format!("warning: {}", warnings.join("; "));
+        }
+    }
+
+    fn selected(&self) -> Option<(usize, &ZenhubIssueInfo)> {
+        let column = self.columns.get(self.focus)?;
+        let row = column.state.selected()?;
+        column.issues.get(row).map(|i| (row, i))
+    }
+
+    fn focus_column(&mut self, delta: isize) {
+        let target = self.focus as isize + delta;
+        if target >= 0 && (target as usize) < self.columns.len() {
+            self.focus = target as usize;
+        }
+    }
+
+    fn select(&mut self, delta: isize) {
+        if let Some(column) = self.columns.get_mut(self.focus) {
+            if column.issues.is_empty() {
+                return;
+            }
+            let row = column.state.selected().unwrap_or(0) as isize + delta;
+            let row = row.clamp(0, column.issues.len() as isize - 1);
+            column.state.select(Some(row as usize));
+        }
+    }
+
+    /// Moves the selected issue to the top of the neighbour pipeline
+    async fn move_selected(&mut self, delta: isize) {
+        let target = self.focus as isize + delta;
+        if target < 0 || target as usize >= self.columns.len() {
+            return;
+        }
+        let target = target as usize;
+        let (row, issue_ref) = match self.selected() {
+            Some((row, i)) => (row, issue_ref(i)),
+            None => return,
+        };
+        let from = self.columns[self.focus].name.clone();
+        let to = self.columns[target].name.clone();
+        let moved = match find_repo_id(&self.repositories, &issue_ref) {
+            Ok(repo_id) => {
+                move_issue_with_retry(
+                    self.opt.clone(),
+                    repo_id,
+                    issue_ref.issue_number,
+                    &self.columns[target].id,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = moved {
+            self.status = format!("{}: move failed: {}", issue_ref, e);
+            return;
+        }
+        audit(&self.opt, "move", &issue_ref, Some(from), &to);
+        let source = &mut self.columns[self.focus];
+        let mut issue = source.issues.remove(row);
+        if source.issues.is_empty() {
+            source.state.select(None);
+        } else if row >= source.issues.len() {
+            source.state.select(Some(source.issues.len() - 1));
+        }
+        if let Some(pipeline) = &mut issue.pipeline {
+            pipeline.name = to.clone();
+            pipeline._id = self.columns[target].id.clone();
+        }
+        let column = &mut self.columns[target];
+        column.issues.insert(0, issue);
+        column.state.select(Some(0));
+        self.status = format!("{} -> {}", issue_ref, to);
+    }
+
+    async fn estimate_selected(&mut self, input: &str) {
+        let value: f32 = match input.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                self.status = format!("invalid estimate {:?}", input);
+                return;
+            }
+        };
+        let (row, issue_ref, old) = match self.selected() {
+            Some((row, i)) => (row, issue_ref(i), i.estimate.map(|e| e.to_string())),
+            None => return,
+        };
+        let updated = match find_repo_id(&self.repositories, &issue_ref) {
+            Ok(repo_id) => {
+                set_estimate(self.opt.clone(), repo_id, issue_ref.issue_number, value).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = updated {
+            self.status = format!("{}: estimate failed: {}", issue_ref, e);
+            return;
+        }
+        audit(&self.opt, "estimate", &issue_ref, old, &value.to_string());
+        self.columns[self.focus].issues[row].estimate = Some(value);
+        self.status = format!("{} estimate {}", issue_ref, value);
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [board, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let n = self.columns.len().max(1) as u32;
+        let areas = Layout::horizontal((0..n).map(|_| Constraint::Ratio(1, n))).split(board);
+        let opt = &self.opt;
+        for (idx, (column, area)) in self.columns.iter_mut().zip(areas.iter()).enumerate() {
+            let estimate: f32 = column.issues.iter().filter_map(|i| i.estimate).sum();
+            let title = format!(
+                " {} ({}, {}h) ",
+                column.name,
+                column.issues.len(),
+                format_estimate(opt, estimate)
+            );
+            let mut block = Block::default().borders(Borders::ALL).title(title);
+            let mut list = List::new(column.issues.iter().map(|i| card(opt, i)));
+            if idx == self.focus {
+                block = block.border_style(Style::default().add_modifier(Modifier::BOLD));
+                list = list.highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+            frame.render_stateful_widget(list.block(block), *area, &mut column.state);
+        }
+        if let Mode::Workspaces(workspaces, state) = &mut self.mode {
+            let area = board.inner(Margin::new(board.width / 4, board.height / 4));
+            let items = workspaces.iter().map(|w| ListItem::new(w.name.as_str()));
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(" workspace "))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_widget(Clear, area);
+            frame.render_stateful_widget(list, area, state);
+        }
+        let line = match &self.mode {
+            Mode::Estimate(input) => format!("estimate: {}_  (Enter to set, Esc to cancel)", input),
+            Mode::Workspaces(..) => "↑↓ workspace  Enter to switch  Esc to cancel".to_string(),
+            Mode::Browse if !self.status.is_empty() => format!("{}  |  {}", self.status, HELP),
+            Mode::Browse => HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        loop {
+            self.show_warnings();
+            terminal.draw(|frame| self.draw(frame))?;
+            let key = match tokio::task::spawn_blocking(event::read).await?? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(());
+            }
+            match &mut self.mode {
+                Mode::Estimate(input) => match key.code {
+                    KeyCode::Esc => self.mode = Mode::Browse,
+                    KeyCode::Enter => {
+                        let input = input.clone();
+                        self.mode = Mode::Browse;
+                        self.estimate_selected(&input).await;
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => input.push(c),
+                    _ => {}
+                },
+                Mode::Workspaces(workspaces, state) => match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Browse,
+                    KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        let last = workspaces.len() - 1;
+                        state.select(Some(state.selected().map_or(0, |n| (n + 1).min(last))));
+                    }
+                    KeyCode::Enter => {
+                        let picked = state.selected().and_then(|n| workspaces.get(n)).cloned();
+                        self.mode = Mode::Browse;
+                        if let Some(workspace) = picked {
+                            self.switch_workspace(workspace).await;
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::Browse => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Left | KeyCode::Char('h') => self.focus_column(-1),
+                    KeyCode::Right | KeyCode::Char('l') => self.focus_column(1),
+                    KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                    KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                    KeyCode::Char('<') | KeyCode::Char('H') => self.move_selected(-1).await,
+                    KeyCode::Char('>') | KeyCode::Char('L') => self.move_selected(1).await,
+                    KeyCode::Char('e') if self.selected().is_some() => {
+                        self.mode = Mode::Estimate(String::new())
+                    }
+                    KeyCode::Char('w') => self.pick_workspace().await,
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+/// Interactive board, the terminal is restored on exit and on errors
+pub async fn run_tui(mut opt: Opt) -> Result<(), Box<dyn Error>> {
+    // the user is looked up once, before the alternate screen: a `User` banner printed
+    // by the lookups of later workspace switches would corrupt the board
+    opt.no_user_banner = true;
+    if let Some(login) = filter_assignee(&opt).await? {
+        opt.assignee = Some(login);
+    }
+    // warnings of the lookups, i.e. of the v4 fallback, go to the status line as well
+    opt.warnings = Some(Arc::new(Mutex::new(vec![])));
+    let mut app = App::load(opt).await?;
+    let mut terminal = ratatui::init();
+    let res = app.run(&mut terminal).await;
+    ratatui::restore();
+    res
+}
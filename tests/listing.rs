@@ -0,0 +1,147 @@
+//! Listings rendered from the API responses in `tests/fixtures`, served by `--fixtures-dir`
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> std::process::Output {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    Command::new(env!("CARGO_BIN_EXE_zenhub"))
+        .args(["--no-dotenv", "--fixtures-dir", fixtures])
+        .args(["--api-token", "token", "--workspace-id", "W"])
+        .args(args)
+        .env_remove("ZENHUB_API_ROOT")
+        .env_remove("ZENHUB_WORKSPACE_NAME")
+        .env_remove("ZENHUB_CONFIG")
+        .env_remove("GITHUB_TOKEN")
+        .env("ZENHUB_NO_UPDATE_CHECK", "1")
+        .env("XDG_CACHE_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .output()
+        .unwrap()
+}
+
+fn zenhub(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn all_pipelines_of_the_assignee() {
+    assert_eq!(
+        zenhub(&["--assignee", "alice", "--all-pipelines"]),
+        "## -- Backlog (count: 1, estimate: 5, not estimated: 0)\n\
+         api:1\t5h\topen\tPayment form\n\
+         ## -- In Progress (count: 1, estimate: 3, not estimated: 0)\n\
+         api:2\t3h\topen\tCard validation\n\
+         ## -- Review (count: 0, estimate: 0, not estimated: 0)\n\
+         (no issues)\n"
+    );
+}
+
+#[test]
+fn label_filter() {
+    let listing = zenhub(&["--assignee", "alice", "--all-pipelines", "--label", "bug"]);
+    assert!(
+        listing.contains("## -- Backlog (count: 0, estimate: 0, not estimated: 0)\n(no issues)\n")
+    );
+    assert!(listing.contains("api:2\t3h\topen\tCard validation\n"));
+    assert!(!listing.contains("api:1"));
+}
+
+#[test]
+fn pipeline_as_csv() {
+    assert_eq!(
+        zenhub(&["--all-assignees", "-p", "In Progress", "--output", "csv"]),
+        "pipeline,repo,number,estimate,state,title\n\
+         In Progress,api,2,3,open,Card validation\n\
+         In Progress,api,3,,open,Refactor routes\n"
+    );
+}
+
+#[test]
+fn select_expression() {
+    assert_eq!(
+        zenhub(&[
+            "--all-assignees",
+            "--all-pipelines",
+            "--no-header",
+            "--select",
+            "estimate > 2 && assignee == \"alice\"",
+        ]),
+        "api:1\t5h\topen\tPayment form\napi:2\t3h\topen\tCard validation\n"
+    );
+}
+
+#[test]
+fn closed_pipelines_are_skipped_unless_asked() {
+    let listing = zenhub(&["--all-assignees", "--all-pipelines"]);
+    assert!(!listing.contains("Closed"));
+    let listing = zenhub(&[
+        "--all-assignees",
+        "--all-pipelines",
+        "--include-closed-pipelines",
+        "--collapse-closed",
+    ]);
+    assert!(listing.ends_with(
+        "## -- Closed (count: 1, estimate: 1, not estimated: 0, all closed, collapsed)\n"
+    ));
+}
+
+#[test]
+fn burndown_as_csv() {
+    assert_eq!(
+        zenhub(&[
+            "--all-assignees",
+            "--all-pipelines",
+            "--include-closed-pipelines",
+            "--output",
+            "csv",
+            "report",
+            "burndown",
+        ]),
+        "pipeline,estimate,closed_estimate,open_estimate,percent_complete\n\
+         Backlog,5,0,5,0\n\
+         In Progress,3,0,3,0\n\
+         Review,0,0,0,\n\
+         Closed,1,1,0,100\n\
+         Total,9,1,8,11\n"
+    );
+}
+
+#[test]
+fn assignee_counts_as_tsv() {
+    assert_eq!(
+        zenhub(&[
+            "--all-assignees",
+            "--all-pipelines",
+            "--output",
+            "tsv",
+            "--assignee-count",
+        ]),
+        "Backlog\t1\talice\t100\tyes\n\
+         In Progress\t2\talice\t100\tyes\n\
+         Review\t0\t\t\tno\n"
+    );
+}
+
+#[test]
+fn validate_as_csv() {
+    let output = run(&["--output", "csv", "validate", "--check", "unestimated"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "check,issue,pipeline,title\nunestimated,api:3,In Progress,Refactor routes\n"
+    );
+}
+
+#[test]
+fn board_rejects_csv() {
+    let output = run(&["--output", "csv", "board"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("csv and tsv are not supported"));
+}